@@ -0,0 +1,46 @@
+//! Compares the compiled pattern matcher with running every pattern on
+//! every line, over this crate's own sources. Run with `cargo bench`.
+
+use codedebt::matcher::PatternMatcher;
+use codedebt::{CodeDebtScanner, Pattern};
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+
+/// Real code, where most lines have no debt.
+fn corpus() -> Vec<(PathBuf, String)> {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    CodeDebtScanner::new()
+        .walked_files(&src)
+        .expect("failed to list sources")
+        .into_iter()
+        .map(|path| {
+            let content = std::fs::read_to_string(&path).expect("failed to read source");
+            (path, content)
+        })
+        .collect()
+}
+
+fn scan(scanner: &CodeDebtScanner, corpus: &[(PathBuf, String)]) -> usize {
+    corpus
+        .iter()
+        .map(|(path, content)| scanner.scan_source(path, content).len())
+        .sum()
+}
+
+fn bench_matcher(c: &mut Criterion) {
+    let corpus = corpus();
+    let compiled = CodeDebtScanner::new();
+    let naive =
+        CodeDebtScanner::new().with_matcher(PatternMatcher::naive(Pattern::default_patterns()));
+
+    let mut group = c.benchmark_group("scan_sources");
+    group.bench_function("compiled", |b| {
+        b.iter(|| scan(&compiled, black_box(&corpus)))
+    });
+    group.bench_function("naive", |b| b.iter(|| scan(&naive, black_box(&corpus))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_matcher);
+criterion_main!(benches);
@@ -0,0 +1,140 @@
+use chrono::NaiveDate;
+
+/// Structured data attached to a debt marker, e.g. `TODO(alice, #12): message`
+/// or `HACK[2025-03-01] remove after migration`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Annotation {
+    pub assignee: Option<String>,
+    pub issue_refs: Vec<String>,
+    pub due_date: Option<NaiveDate>,
+    pub message: Option<String>,
+}
+
+impl Annotation {
+    /// Parse the text that follows a matched marker on the same line.
+    pub fn parse(rest: &str) -> Self {
+        let mut annotation = Annotation::default();
+        let mut rest = rest;
+
+        let trimmed = rest.trim_start();
+        let close = match trimmed.chars().next() {
+            Some('(') => Some(')'),
+            Some('[') => Some(']'),
+            _ => None,
+        };
+        if let Some(close) = close {
+            if let Some(end) = trimmed.find(close) {
+                for token in trimmed[1..end].split(|c: char| c == ',' || c.is_whitespace()) {
+                    annotation.add_token(token);
+                }
+                rest = &trimmed[end + 1..];
+            }
+        }
+
+        let message = rest
+            .trim()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim()
+            .trim_start_matches([':', '-'])
+            .trim();
+        if !message.is_empty() {
+            annotation.message = Some(message.to_string());
+        }
+
+        annotation
+    }
+
+    fn add_token(&mut self, token: &str) {
+        let token = token.trim();
+        if token.is_empty() {
+            return;
+        }
+
+        if is_issue_ref(token) {
+            self.issue_refs.push(token.to_string());
+        } else if let Ok(date) = NaiveDate::parse_from_str(token, "%Y-%m-%d") {
+            self.due_date = Some(date);
+        } else if self.assignee.is_none() {
+            self.assignee = Some(token.trim_start_matches('@').to_string());
+        }
+    }
+}
+
+/// `#123`, `owner/repo#123`, `JIRA-88` or an issue URL.
+fn is_issue_ref(token: &str) -> bool {
+    if token.starts_with("http://") || token.starts_with("https://") {
+        return true;
+    }
+
+    if let Some((prefix, number)) = token.rsplit_once('#') {
+        let valid_prefix = prefix.is_empty()
+            || prefix
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '_' | '.'));
+        return valid_prefix && is_number(number);
+    }
+
+    if let Some((project, number)) = token.rsplit_once('-') {
+        return project
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_uppercase())
+            && project
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+            && is_number(number);
+    }
+
+    false
+}
+
+fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_assignee_and_message() {
+        let annotation = Annotation::parse("(alice): handle errors");
+        assert_eq!(annotation.assignee.as_deref(), Some("alice"));
+        assert!(annotation.issue_refs.is_empty());
+        assert_eq!(annotation.message.as_deref(), Some("handle errors"));
+    }
+
+    #[test]
+    fn test_parse_issue_refs() {
+        assert_eq!(Annotation::parse("(#1234)").issue_refs, vec!["#1234"]);
+        assert_eq!(Annotation::parse("(JIRA-88)").issue_refs, vec!["JIRA-88"]);
+        assert_eq!(
+            Annotation::parse("(@bob, acme/api#7)"),
+            Annotation {
+                assignee: Some("bob".to_string()),
+                issue_refs: vec!["acme/api#7".to_string()],
+                due_date: None,
+                message: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_due_date() {
+        let annotation = Annotation::parse("[2025-03-01] remove after migration */");
+        assert_eq!(annotation.due_date, NaiveDate::from_ymd_opt(2025, 3, 1));
+        assert_eq!(
+            annotation.message.as_deref(),
+            Some("remove after migration")
+        );
+    }
+
+    #[test]
+    fn test_parse_plain_marker() {
+        let annotation = Annotation::parse(": just a note");
+        assert_eq!(annotation.assignee, None);
+        assert_eq!(annotation.message.as_deref(), Some("just a note"));
+        assert_eq!(Annotation::parse("   "), Annotation::default());
+    }
+}
@@ -0,0 +1,291 @@
+use crate::fingerprint::{self, normalize};
+use crate::git::GitAnalyzer;
+use crate::models::CodeDebtItem;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_BASELINE_FILE: &str = ".codedebt-baseline.json";
+/// Version 2 fingerprints are path-free and include surrounding lines.
+/// Version 1 files still load and are matched by line content.
+const BASELINE_VERSION: u32 = 2;
+
+/// A snapshot of accepted debt. Scans compared against it only report items
+/// that are not already recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    /// HEAD when the baseline was created, used to follow renamed files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub entries: Vec<BaselineEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub fingerprint: String,
+    pub file_path: PathBuf,
+    pub pattern_type: String,
+    pub line_content: String,
+}
+
+impl Baseline {
+    /// Build a baseline with file paths stored relative to `root`, normally
+    /// the directory containing the baseline file.
+    pub fn from_items(items: &[CodeDebtItem], root: &Path) -> Self {
+        let root = absolute(root);
+        let entries = items
+            .iter()
+            .map(|item| BaselineEntry {
+                fingerprint: item_fingerprint(item),
+                file_path: relative_path(&item.file_path, &root),
+                pattern_type: item.pattern_type.clone(),
+                line_content: item.line_content.clone(),
+            })
+            .collect();
+        let commit = Repository::discover(&root)
+            .and_then(|repo| Ok(repo.head()?.peel_to_commit()?.id().to_string()))
+            .ok();
+
+        Self {
+            version: BASELINE_VERSION,
+            created_at: Utc::now(),
+            commit,
+            entries,
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read baseline {}", path.display()))?;
+        let baseline: Baseline = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid baseline file {}", path.display()))?;
+        if baseline.version > BASELINE_VERSION {
+            anyhow::bail!(
+                "Baseline {} has version {}, expected at most {}. Upgrade codedebt or recreate it with 'codedebt baseline create'",
+                path.display(),
+                baseline.version,
+                BASELINE_VERSION
+            );
+        }
+        Ok(baseline)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, content + "\n")
+            .with_context(|| format!("Failed to write baseline {}", path.display()))
+    }
+
+    /// Items not covered by the baseline. Each entry absorbs at most one item
+    /// with the same fingerprint, so adding a copy of a baselined TODO is
+    /// still reported.
+    ///
+    /// Entries are matched in the same file, following renames since the
+    /// baseline's commit, by fingerprint first and then by pattern and line
+    /// content alone, so editing a line next to an item does not make it new.
+    pub fn new_items(&self, items: &[CodeDebtItem], root: &Path) -> Vec<CodeDebtItem> {
+        let root = absolute(root);
+        let renames = self.renames(&root);
+
+        let mut by_fingerprint: HashMap<(PathBuf, String), Vec<usize>> = HashMap::new();
+        let mut by_content: HashMap<(PathBuf, &str, String), Vec<usize>> = HashMap::new();
+        for (idx, entry) in self.entries.iter().enumerate().rev() {
+            let path = renames
+                .get(&entry.file_path)
+                .unwrap_or(&entry.file_path)
+                .clone();
+            by_fingerprint
+                .entry((path.clone(), entry.fingerprint.clone()))
+                .or_default()
+                .push(idx);
+            by_content
+                .entry((
+                    path,
+                    entry.pattern_type.as_str(),
+                    normalize(&entry.line_content),
+                ))
+                .or_default()
+                .push(idx);
+        }
+
+        let mut used = vec![false; self.entries.len()];
+        let mut take = |candidates: Option<&mut Vec<usize>>| {
+            let candidates = candidates?;
+            while let Some(idx) = candidates.pop() {
+                if !used[idx] {
+                    used[idx] = true;
+                    return Some(idx);
+                }
+            }
+            None
+        };
+
+        let paths: Vec<PathBuf> = items
+            .iter()
+            .map(|item| relative_path(&item.file_path, &root))
+            .collect();
+        let mut matched: Vec<bool> = items
+            .iter()
+            .zip(&paths)
+            .map(|(item, path)| {
+                take(by_fingerprint.get_mut(&(path.clone(), item_fingerprint(item)))).is_some()
+            })
+            .collect();
+        for ((item, path), matched) in items.iter().zip(&paths).zip(&mut matched) {
+            if !*matched {
+                let key = (
+                    path.clone(),
+                    item.pattern_type.as_str(),
+                    normalize(&item.line_content),
+                );
+                *matched = take(by_content.get_mut(&key)).is_some();
+            }
+        }
+
+        items
+            .iter()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+            .map(|(item, _)| item.clone())
+            .collect()
+    }
+
+    /// Renames since the baseline's commit, as old to new path relative to
+    /// `root`.
+    fn renames(&self, root: &Path) -> HashMap<PathBuf, PathBuf> {
+        let Some(commit) = &self.commit else {
+            return HashMap::new();
+        };
+        let Ok(repo) = Repository::discover(root) else {
+            return HashMap::new();
+        };
+        let Some(workdir) = repo.workdir().map(absolute) else {
+            return HashMap::new();
+        };
+        match GitAnalyzer::renames_since(&repo, commit) {
+            Ok(renames) => renames
+                .into_iter()
+                .map(|(old, new)| {
+                    (
+                        relative_path(&workdir.join(old), root),
+                        relative_path(&workdir.join(new), root),
+                    )
+                })
+                .collect(),
+            Err(e) => {
+                log::debug!("Not following renames since {}: {}", commit, e);
+                HashMap::new()
+            }
+        }
+    }
+}
+
+/// The item's fingerprint, or one from its line alone for items that were
+/// not produced by a scan.
+fn item_fingerprint(item: &CodeDebtItem) -> String {
+    if item.fingerprint.is_empty() {
+        fingerprint::fingerprint(&item.pattern_type, &item.line_content, None, None)
+    } else {
+        item.fingerprint.clone()
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| {
+        std::env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    })
+}
+
+fn relative_path(path: &Path, root: &Path) -> PathBuf {
+    let path = absolute(path);
+    path.strip_prefix(root)
+        .map(Path::to_path_buf)
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Severity;
+    use crate::test_support::{commit, now};
+
+    fn item(path: &str, line: usize, content: &str) -> CodeDebtItem {
+        CodeDebtItem::new(
+            PathBuf::from(path),
+            line,
+            1,
+            content,
+            "TODO",
+            Severity::Medium,
+        )
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_line_number_and_whitespace() {
+        let root = Path::new("/repo");
+        let a = item("/repo/src/lib.rs", 10, "// TODO: fix  this");
+        let b = item("/repo/src/lib.rs", 42, "//   TODO: fix this");
+        let c = item("/repo/src/main.rs", 10, "// TODO: fix this");
+
+        let baseline = Baseline::from_items(&[a], root);
+        assert!(baseline.new_items(&[b], root).is_empty());
+        assert_eq!(baseline.new_items(&[c], root).len(), 1);
+    }
+
+    #[test]
+    fn test_duplicates_are_counted() {
+        let root = Path::new("/repo");
+        let baseline = Baseline::from_items(&[item("/repo/a.rs", 1, "// TODO")], root);
+
+        let current = vec![
+            item("/repo/a.rs", 1, "// TODO"),
+            item("/repo/a.rs", 5, "// TODO"),
+        ];
+        let new_items = baseline.new_items(&current, root);
+        assert_eq!(new_items.len(), 1);
+        assert_eq!(new_items[0].line_number, 5);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join(DEFAULT_BASELINE_FILE);
+        let baseline = Baseline::from_items(&[item("a.rs", 1, "// TODO")], temp_dir.path());
+        baseline.save(&path).unwrap();
+
+        let loaded = Baseline::load(&path).unwrap();
+        assert_eq!(loaded.entries.len(), 1);
+        assert_eq!(
+            loaded.entries[0].fingerprint,
+            baseline.entries[0].fingerprint
+        );
+    }
+
+    #[test]
+    fn test_renamed_and_shifted_items_are_not_new() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let repo = Repository::init(root).unwrap();
+        let content = "fn a() {\n    // TODO: handle errors\n}\n\nfn b() {}\n";
+        commit(&repo, &[("old.rs", Some(content))], "Dev", now());
+
+        let scanner = crate::CodeDebtScanner::new();
+        let baseline = Baseline::from_items(&scanner.scan(root).unwrap(), root);
+        assert!(baseline.commit.is_some());
+
+        std::fs::remove_file(root.join("old.rs")).unwrap();
+        std::fs::write(root.join("new.rs"), format!("use std::fs;\n\n{}", content)).unwrap();
+        assert!(baseline
+            .new_items(&scanner.scan(root).unwrap(), root)
+            .is_empty());
+    }
+}
@@ -1,47 +1,51 @@
-use clap::{Parser, ValueEnum};
-use codedebt::{CodeDebtScanner, Severity};
+use anyhow::anyhow;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use codedebt::baseline::Baseline;
+use codedebt::blame::AgeFrom;
+use codedebt::config::Defaults;
+use codedebt::encoding::{decode, source_offsets, Decoded, Encoding};
+use codedebt::error::CodeDebtError;
+use codedebt::history::{debt_history, HistoryOptions, HistoryPoint, Sampling};
+use codedebt::models::Hotspot;
+use codedebt::progress::{JsonProgressReporter, TerminalProgressReporter};
+use codedebt::report::{SkipReason, SkippedFile, TranscodedFile};
+use codedebt::resolution::{resolved_since, ResolutionReport};
+use codedebt::revision::open_repository;
+use codedebt::thresholds::Thresholds;
+use codedebt::{BlameStatus, CodeDebtItem, CodeDebtScanner, Config, Pattern, ScanReport, Severity};
 use colored::*;
 use glob::glob;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(name = "codedebt")]
 #[command(about = "Ultra-fast code debt detection tool")]
 #[command(version)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
-    /// Directory or glob pattern to scan
-    #[arg(default_value = ".")]
-    path: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    scan: ScanArgs,
 
-    /// Minimum severity level to show
-    #[arg(short, long, value_enum, default_value = "low")]
-    severity: SeverityArg,
+    /// Minimum severity level to show [default: low]
+    #[arg(short, long, value_enum)]
+    severity: Option<SeverityArg>,
 
-    /// Output format
-    #[arg(short, long, value_enum, default_value = "pretty")]
-    format: OutputFormat,
+    /// Output format [default: pretty]
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
 
     /// Show summary only
     #[arg(short = 'S', long)]
     summary: bool,
 
-    /// File extensions to scan (comma-separated)
-    #[arg(short, long)]
-    extensions: Option<String>,
-
-    /// Additional directories to ignore (comma-separated)
-    #[arg(short, long)]
-    ignore: Option<String>,
-
-    /// Enable git blame integration for age detection
-    #[arg(long)]
-    git_blame: bool,
-
-    /// Enable duplicate pattern detection
-    #[arg(long)]
-    detect_duplicates: bool,
-
     /// Show file type distribution
     #[arg(long)]
     file_types: bool,
@@ -54,10 +58,56 @@ struct Cli {
     #[arg(long)]
     max_age: Option<i64>,
 
+    /// Show only items with these blame statuses, e.g. uncommitted,untracked
+    /// (requires --git-blame)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    blame_status: Vec<BlameStatusArg>,
+
+    /// Show only items assigned to this person, e.g. TODO(alice)
+    #[arg(long)]
+    assignee: Option<String>,
+
+    /// Show only items referencing this issue, e.g. #123 or JIRA-88
+    #[arg(long)]
+    issue: Option<String>,
+
+    /// Show only items due on or before this date (YYYY-MM-DD)
+    #[arg(long)]
+    due_before: Option<NaiveDate>,
+
     /// Show only duplicates with minimum count
     #[arg(long)]
     min_duplicates: Option<usize>,
 
+    /// Only report items missing from this baseline file and exit with
+    /// status 1 if there are any
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+
+    /// Only report debt added since the merge-base of HEAD and this git ref
+    #[arg(long, visible_alias = "diff-base", value_name = "REF")]
+    since: Option<String>,
+
+    /// With --since, also list debt removed in that range
+    #[arg(long, requires = "since")]
+    show_resolved: bool,
+
+    /// List items silenced by codedebt:ignore directives
+    #[arg(long)]
+    report_suppressed: bool,
+
+    /// Exit with status 1 if any item is at least this severe
+    #[arg(long, value_enum)]
+    fail_on: Option<SeverityArg>,
+
+    /// Exit with status 1 if more than this many items are reported
+    #[arg(long)]
+    max_items: Option<usize>,
+
+    /// Per-severity limits, e.g. critical=0,high=10 (exit status 1 if exceeded)
+    #[arg(long, value_name = "LIMITS")]
+    max: Option<String>,
+
     /// Enable watch mode
     #[arg(short, long)]
     watch: bool,
@@ -65,10 +115,177 @@ struct Cli {
     /// Enable interactive mode
     #[arg(short = 'I', long)]
     interactive: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage baseline files of accepted debt
+    Baseline {
+        #[command(subcommand)]
+        action: BaselineAction,
+    },
+    /// Count debt over the git history, read straight from the object
+    /// database; walks the first-parent history from --rev [default: HEAD]
+    History {
+        /// Count at every Nth commit
+        #[arg(long, default_value_t = 1, conflicts_with = "tags")]
+        every: usize,
+
+        /// Count at every tag instead of walking the history
+        #[arg(long)]
+        tags: bool,
+
+        /// Keep only the most recent points
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: ReportFormat,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+    /// List debt removed since a revision, with who removed it and when;
+    /// the range ends at --rev [default: HEAD]
+    Resolved {
+        /// Revision to start after, e.g. a release tag
+        #[arg(long)]
+        since: String,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: ReportFormat,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+    /// Rank files by debt times how often they changed recently, to find
+    /// the debt in code that is actually being worked on
+    Hotspots {
+        /// Count commits from this many days before now (or --now)
+        #[arg(long, default_value_t = 90)]
+        days: i64,
+
+        /// Show only the top files
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: ReportFormat,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum BaselineAction {
+    /// Record every current item in a baseline file
+    Create {
+        /// Baseline file to write
+        #[arg(short, long, default_value = codedebt::baseline::DEFAULT_BASELINE_FILE)]
+        output: PathBuf,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+}
+
+/// Options that control what gets scanned, shared by all commands.
+#[derive(Args)]
+struct ScanArgs {
+    /// Directories, files or glob patterns to scan; `-` reads from stdin
+    #[arg(default_value = ".")]
+    paths: Vec<String>,
+
+    /// Name to report for content read from stdin; its extension selects
+    /// the language
+    #[arg(long, value_name = "PATH")]
+    stdin_filename: Option<PathBuf>,
+
+    /// Scan the tree of this commit, tag or branch from the object database
+    /// instead of the working directory; works in bare repositories
+    #[arg(long, value_name = "REV")]
+    rev: Option<String>,
+
+    /// File extensions to scan (comma-separated)
+    #[arg(short, long)]
+    extensions: Option<String>,
 
-    /// Show progress indicator for large repositories
+    /// Additional directories to ignore (comma-separated), replacing
+    /// `ignore_dirs` from the config file
+    #[arg(short, long)]
+    ignore: Option<String>,
+
+    /// Only scan files matching this glob (gitignore syntax, repeatable),
+    /// replacing `include` from the config file
+    #[arg(long, value_name = "GLOB")]
+    include: Vec<String>,
+
+    /// Skip files and directories matching this glob (gitignore syntax,
+    /// repeatable), replacing `exclude` from the config file
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Also report matches in code and string literals, not just comments
+    #[arg(long)]
+    include_code: bool,
+
+    /// Report every match of a pattern on a line, not just the first
+    #[arg(long)]
+    all_matches: bool,
+
+    /// Enable git blame integration for age detection
+    #[arg(long)]
+    git_blame: bool,
+
+    /// Blame past this commit, e.g. a mass reformat (repeatable)
+    #[arg(long, value_name = "REV")]
+    blame_ignore_rev: Vec<String>,
+
+    /// File of commits to blame past [default: .git-blame-ignore-revs]
+    #[arg(long, value_name = "FILE")]
+    blame_ignore_revs_file: Option<PathBuf>,
+
+    /// Ignore whitespace-only changes when blaming
+    #[arg(long)]
+    blame_ignore_whitespace: bool,
+
+    /// Follow lines moved or copied within a commit when blaming
+    #[arg(long)]
+    blame_track_moves: bool,
+
+    /// Measure ages from the commit date or the original author date
+    #[arg(long, value_enum)]
+    age_from: Option<AgeFromArg>,
+
+    /// Measure ages up to this time instead of now, for reproducible
+    /// reports (RFC 3339 or YYYY-MM-DD)
+    #[arg(long, value_name = "TIMESTAMP", value_parser = parse_reference_time)]
+    now: Option<DateTime<Utc>>,
+
+    /// Enable duplicate pattern detection
+    #[arg(long)]
+    detect_duplicates: bool,
+
+    /// Show a progress bar on stderr (hidden when stderr is not a terminal)
     #[arg(long)]
     progress: bool,
+
+    /// How to report progress; json writes one event per line to stderr
+    /// and implies --progress
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    progress_format: Option<ProgressFormat>,
+
+    /// Config file to use instead of discovering .codedebt.toml
+    #[arg(long, conflicts_with = "no_config")]
+    config: Option<PathBuf>,
+
+    /// Ignore any .codedebt.toml file
+    #[arg(long)]
+    no_config: bool,
 }
 
 #[derive(Clone, ValueEnum)]
@@ -90,28 +307,184 @@ impl From<SeverityArg> for Severity {
     }
 }
 
+#[derive(Clone, ValueEnum)]
+enum AgeFromArg {
+    Commit,
+    Author,
+}
+
+impl From<AgeFromArg> for AgeFrom {
+    fn from(arg: AgeFromArg) -> Self {
+        match arg {
+            AgeFromArg::Commit => AgeFrom::Commit,
+            AgeFromArg::Author => AgeFrom::Author,
+        }
+    }
+}
+
+#[derive(Clone, ValueEnum)]
+enum BlameStatusArg {
+    Committed,
+    Uncommitted,
+    Untracked,
+    OutsideRepo,
+    Error,
+}
+
+impl From<BlameStatusArg> for BlameStatus {
+    fn from(arg: BlameStatusArg) -> Self {
+        match arg {
+            BlameStatusArg::Committed => BlameStatus::Committed,
+            BlameStatusArg::Uncommitted => BlameStatus::Uncommitted,
+            BlameStatusArg::Untracked => BlameStatus::Untracked,
+            BlameStatusArg::OutsideRepo => BlameStatus::OutsideRepo,
+            BlameStatusArg::Error => BlameStatus::Error,
+        }
+    }
+}
+
 #[derive(Clone, ValueEnum)]
 enum OutputFormat {
     Pretty,
     Json,
     Csv,
+    Sarif,
 }
 
-fn main() -> anyhow::Result<()> {
+#[derive(Clone, ValueEnum)]
+enum ProgressFormat {
+    Bar,
+    Json,
+}
+
+#[derive(Clone, ValueEnum)]
+enum ReportFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+/// Exit status when a threshold is exceeded or a baseline comparison finds new debt
+const EXIT_THRESHOLD_EXCEEDED: u8 = 1;
+/// Exit status when the scan itself fails
+const EXIT_SCAN_ERROR: u8 = 2;
+
+/// A configured scanner plus the settings resolved from flags and config.
+struct Setup {
+    scanner: CodeDebtScanner,
+    paths: Vec<PathBuf>,
+    /// Name for content read from stdin, if `-` was given
+    stdin: Option<PathBuf>,
+    /// Revision to scan instead of the working directory
+    rev: Option<String>,
+    defaults: Defaults,
+    git_blame: bool,
+    detect_duplicates: bool,
+}
+
+fn main() -> ExitCode {
     // Initialize logger if RUST_LOG env var is set
     env_logger::init();
 
     let cli = Cli::parse();
 
+    let result = match cli.command {
+        Some(Command::Baseline {
+            action: BaselineAction::Create { output, scan },
+        }) => create_baseline(&scan, &output),
+        Some(Command::History {
+            every,
+            tags,
+            limit,
+            format,
+            scan,
+        }) => {
+            let sampling = if tags {
+                Sampling::Tags
+            } else {
+                Sampling::FirstParent { every }
+            };
+            let options = HistoryOptions {
+                rev: scan.rev.clone().unwrap_or_else(|| "HEAD".to_string()),
+                sampling,
+                limit,
+                prefix: None,
+            };
+            run_history(&scan, options, &format)
+        }
+        Some(Command::Resolved {
+            since,
+            format,
+            scan,
+        }) => run_resolved(&scan, &since, &format),
+        Some(Command::Hotspots {
+            days,
+            limit,
+            format,
+            scan,
+        }) => run_hotspots(&scan, days, limit, &format),
+        None => run_scan(cli),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            if e.downcast_ref::<CodeDebtError>().is_some() {
+                eprintln!("{}", e);
+            } else {
+                eprintln!("{} {:#}", "Error:".red().bold(), e);
+            }
+            ExitCode::from(EXIT_SCAN_ERROR)
+        }
+    }
+}
+
+fn setup(args: &ScanArgs, show_progress: bool) -> anyhow::Result<Setup> {
+    let stdin = args.paths.iter().any(|path| path == "-").then(|| {
+        args.stdin_filename
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("<stdin>"))
+    });
+
+    if stdin.is_some() && args.rev.is_some() {
+        anyhow::bail!("--rev cannot be combined with reading from stdin");
+    }
+
     // Handle glob patterns
-    let paths = resolve_paths(&cli.path)?;
-    if paths.is_empty() {
-        return Err(codedebt::error::handle_path_error(&cli.path).into());
+    let mut paths = Vec::new();
+    for pattern in args.paths.iter().filter(|path| *path != "-") {
+        let resolved = resolve_paths(pattern)?;
+        if resolved.is_empty() {
+            return Err(codedebt::error::handle_path_error(pattern).into());
+        }
+        paths.extend(resolved);
     }
+    let paths = dedup_paths(paths);
+
+    let config = if args.no_config {
+        None
+    } else if let Some(config_path) = &args.config {
+        Some(Config::load(config_path)?)
+    } else {
+        let start = paths.first().or(stdin.as_ref()).cloned();
+        Config::discover(start.unwrap_or_else(|| PathBuf::from(".")))?.map(|(_, config)| config)
+    };
 
     let mut scanner = CodeDebtScanner::new();
+    if let Some(config) = &config {
+        scanner = config.apply(scanner)?;
+    }
 
-    if let Some(extensions) = cli.extensions {
+    // Command line flags take precedence over config file defaults
+    let defaults = config
+        .as_ref()
+        .map(|c| c.defaults.clone())
+        .unwrap_or_default();
+    let git_blame = args.git_blame || defaults.git_blame;
+    let detect_duplicates = args.detect_duplicates || defaults.detect_duplicates;
+    let progress = args.progress || args.progress_format.is_some() || defaults.progress;
+
+    if let Some(extensions) = &args.extensions {
         let exts: Vec<String> = extensions
             .split(',')
             .map(|s| s.trim().to_string())
@@ -119,157 +492,657 @@ fn main() -> anyhow::Result<()> {
         scanner = scanner.with_file_extensions(exts);
     }
 
-    if let Some(ignore_dirs) = cli.ignore {
-        let dirs: Vec<String> = ignore_dirs
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .collect();
+    // Lists from the command line replace those from the config file, but
+    // the built-in ignored directories are always kept
+    if let Some(ignore_dirs) = &args.ignore {
+        let mut dirs = Pattern::default_ignore_dirs();
+        dirs.extend(ignore_dirs.split(',').map(|s| s.trim().to_string()));
         scanner = scanner.with_ignore_dirs(dirs);
     }
 
+    if !args.include.is_empty() {
+        scanner = scanner.with_include(args.include.clone());
+    }
+
+    if !args.exclude.is_empty() {
+        scanner = scanner.with_exclude(args.exclude.clone());
+    }
+
+    if args.include_code {
+        scanner = scanner.with_comments_only(false);
+    }
+
+    if args.all_matches {
+        scanner = scanner.with_all_matches(true);
+    }
+
+    if git_blame && args.rev.is_some() {
+        eprintln!("Warning: --git-blame has no effect when scanning a revision with --rev");
+    }
+
     // Configure enhanced intelligence features
-    if cli.git_blame {
-        scanner = scanner.with_git_blame(true);
+    if git_blame {
+        let mut blame = scanner.blame_config().clone();
+        blame
+            .ignore_revs
+            .extend(args.blame_ignore_rev.iter().cloned());
+        if let Some(file) = &args.blame_ignore_revs_file {
+            blame.ignore_revs_file = Some(file.clone());
+        }
+        blame.ignore_whitespace |= args.blame_ignore_whitespace;
+        blame.track_moves |= args.blame_track_moves;
+        if let Some(age_from) = &args.age_from {
+            blame.age_from = age_from.clone().into();
+        }
+        scanner = scanner.with_git_blame(true).with_blame_config(blame);
+    }
+
+    if let Some(now) = args.now {
+        scanner = scanner.with_reference_time(now);
     }
 
-    if cli.detect_duplicates {
+    if detect_duplicates {
         scanner = scanner.with_duplicate_detection(true);
     }
 
     // Add progress reporter if requested
-    if cli.progress && !cli.watch && !cli.interactive {
-        scanner = scanner.with_progress_reporter(Box::new(
-            codedebt::progress::TerminalProgressReporter::new(true),
-        ));
+    if progress && show_progress {
+        scanner = match args.progress_format {
+            Some(ProgressFormat::Json) => {
+                scanner.with_progress_reporter(Box::new(JsonProgressReporter::new()))
+            }
+            _ => scanner.with_progress_reporter(Box::new(TerminalProgressReporter::new(true))),
+        };
+    }
+
+    Ok(Setup {
+        scanner,
+        paths,
+        stdin,
+        rev: args.rev.clone(),
+        defaults,
+        git_blame,
+        detect_duplicates,
+    })
+}
+
+/// Scan every path and stdin if requested, returning the combined report
+/// and the number of scans that failed.
+fn scan_all(
+    scanner: &CodeDebtScanner,
+    paths: &[PathBuf],
+    stdin: Option<&Path>,
+    rev: Option<&str>,
+) -> (ScanReport, usize) {
+    let mut report = ScanReport::new(Vec::new(), scanner.config_hash());
+    let mut errors = 0;
+
+    if let Some(rev) = rev {
+        for path in paths {
+            match scanner.scan_revision_report(path, rev) {
+                Ok(scanned) => report.merge(scanned),
+                Err(e) => {
+                    eprintln!("Error scanning {} at {}: {:#}", path.display(), rev, e);
+                    errors += 1;
+                }
+            }
+        }
+        return (report, errors);
+    }
+
+    // Files given explicitly are scanned as-is, directories are walked
+    let (mut files, dirs): (Vec<PathBuf>, Vec<PathBuf>) =
+        paths.iter().cloned().partition(|path| path.is_file());
+
+    // Files the walk of a directory also given will read are scanned once
+    for dir in &dirs {
+        let dir = canonical(dir);
+        if !files.iter().any(|file| canonical(file).starts_with(&dir)) {
+            continue;
+        }
+        let walked: HashSet<PathBuf> = match scanner.walked_files(&dir) {
+            Ok(walked) => walked.iter().map(|path| canonical(path)).collect(),
+            Err(_) => continue,
+        };
+        files.retain(|file| !walked.contains(&canonical(file)));
+    }
+
+    for path in &dirs {
+        match scanner.scan_report(path) {
+            Ok(scanned) => report.merge(scanned),
+            Err(e) => {
+                eprintln!("Error scanning {}: {}", path.display(), e);
+                errors += 1;
+            }
+        }
+    }
+
+    if !files.is_empty() {
+        let scanned = scanner.scan_files_report(&files);
+        // Unlike files found by walking, files asked for by name must be read
+        for skipped in &scanned.files_skipped {
+            eprintln!(
+                "Error scanning {}: {}",
+                skipped.path.display(),
+                skipped.reason
+            );
+            errors += 1;
+        }
+        report.merge(scanned);
+    }
+
+    if let Some(name) = stdin {
+        let mut bytes = Vec::new();
+        match std::io::stdin().read_to_end(&mut bytes) {
+            Ok(_) => {
+                report.roots.push(name.to_path_buf());
+                match decode(&bytes) {
+                    Decoded::Text {
+                        content,
+                        encoding,
+                        lossy,
+                    } => {
+                        report.files_scanned += 1;
+                        report.bytes_read += bytes.len() as u64;
+                        let mut items = scanner.scan_source(name, &content);
+                        source_offsets(
+                            &bytes,
+                            &content,
+                            encoding,
+                            items.iter_mut().map(|item| &mut item.byte_offset),
+                        );
+                        report.items.extend(items);
+                        if encoding != Encoding::Utf8 || lossy {
+                            report.files_transcoded.push(TranscodedFile {
+                                path: name.to_path_buf(),
+                                encoding,
+                                lossy,
+                            });
+                        }
+                    }
+                    Decoded::Binary => {
+                        eprintln!("Error scanning {}: binary", name.display());
+                        report.files_skipped.push(SkippedFile {
+                            path: name.to_path_buf(),
+                            reason: SkipReason::Binary,
+                        });
+                        errors += 1;
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Error reading stdin: {}", e);
+                errors += 1;
+            }
+        }
+    }
+
+    report.dedup_items();
+    (report, errors)
+}
+
+fn create_baseline(args: &ScanArgs, output: &Path) -> anyhow::Result<ExitCode> {
+    let Setup {
+        scanner,
+        paths,
+        stdin,
+        rev,
+        ..
+    } = setup(args, true)?;
+    let (report, errors) = scan_all(&scanner, &paths, stdin.as_deref(), rev.as_deref());
+    if errors > 0 {
+        anyhow::bail!("Not writing a baseline because scanning failed");
+    }
+    let items = report.items;
+
+    let root = baseline_root(output);
+    let baseline = Baseline::from_items(&items, &root);
+    baseline.save(output)?;
+
+    println!(
+        "{} Wrote baseline with {} items to {}",
+        "✅".green(),
+        baseline.entries.len(),
+        output.display()
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_history(
+    args: &ScanArgs,
+    mut options: HistoryOptions,
+    format: &ReportFormat,
+) -> anyhow::Result<ExitCode> {
+    let Setup {
+        scanner,
+        paths,
+        stdin,
+        ..
+    } = setup(args, false)?;
+    if stdin.is_some() || paths.len() != 1 {
+        anyhow::bail!("history takes a single repository path");
+    }
+
+    // Scanning a subdirectory counts only the files under it
+    let (repo, prefix) = open_repository(&paths[0])?;
+    options.prefix = prefix;
+
+    let points = debt_history(&scanner, &repo, &options)?;
+    match format {
+        ReportFormat::Table => print_history_table(&points),
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&points)?),
+        ReportFormat::Csv => print_history_csv(&points),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_resolved(args: &ScanArgs, since: &str, format: &ReportFormat) -> anyhow::Result<ExitCode> {
+    let Setup {
+        scanner,
+        paths,
+        stdin,
+        rev,
+        ..
+    } = setup(args, false)?;
+    if stdin.is_some() || paths.len() != 1 {
+        anyhow::bail!("resolved takes a single repository path");
     }
 
+    let (repo, prefix) = open_repository(&paths[0])?;
+    let rev = rev.as_deref().unwrap_or("HEAD");
+    let report = resolved_since(&scanner, &repo, since, rev, prefix.as_deref())?;
+    match format {
+        ReportFormat::Table => print_resolution_table(&report, since),
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        ReportFormat::Csv => print_resolution_csv(&report),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_hotspots(
+    args: &ScanArgs,
+    days: i64,
+    limit: usize,
+    format: &ReportFormat,
+) -> anyhow::Result<ExitCode> {
+    let Setup {
+        scanner,
+        paths,
+        stdin,
+        rev,
+        ..
+    } = setup(args, true)?;
+    if stdin.is_some() || rev.is_some() {
+        anyhow::bail!("hotspots scans the working directory, not stdin or --rev");
+    }
+
+    let (report, errors) = scan_all(&scanner, &paths, None, None);
+    if errors > 0 {
+        anyhow::bail!("Not ranking hotspots because scanning failed");
+    }
+    let items = report.items;
+
+    let mut hotspots = scanner.get_hotspots(&items, days);
+    hotspots.truncate(limit);
+    match format {
+        ReportFormat::Table => print_hotspot_table(&hotspots, days),
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&hotspots)?),
+        ReportFormat::Csv => print_hotspot_csv(&hotspots),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Baseline paths are stored relative to the directory holding the file.
+fn baseline_root(baseline_path: &Path) -> PathBuf {
+    match baseline_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+fn run_scan(cli: Cli) -> anyhow::Result<ExitCode> {
+    let Setup {
+        scanner,
+        paths,
+        stdin,
+        rev,
+        defaults,
+        git_blame,
+        detect_duplicates,
+    } = setup(&cli.scan, !cli.watch && !cli.interactive)?;
+
+    let severity: Severity = match cli.severity {
+        Some(severity) => severity.into(),
+        None => defaults.severity.unwrap_or(Severity::Low),
+    };
+    let format = match (cli.format, &defaults.format) {
+        (Some(format), _) => format,
+        (None, Some(name)) => OutputFormat::from_str(name, true)
+            .map_err(|_| anyhow!("Invalid output format '{}' in config file", name))?,
+        (None, None) => OutputFormat::Pretty,
+    };
+    let summary = cli.summary || defaults.summary;
+    let thresholds = Thresholds {
+        fail_on: cli.fail_on.map(Severity::from).or(defaults.fail_on.clone()),
+        max_items: cli.max_items.or(defaults.max_items),
+        max_per_severity: match &cli.max {
+            Some(spec) => Thresholds::parse_limits(spec)?,
+            None => defaults.max.clone(),
+        },
+    };
+    let file_types = cli.file_types || defaults.file_types;
+    let age_distribution = cli.age_distribution || defaults.age_distribution;
+    let report_suppressed = cli.report_suppressed || defaults.report_suppressed;
+
     // Handle watch mode
     if cli.watch {
+        if stdin.is_some() {
+            anyhow::bail!("Watch mode cannot read from stdin");
+        }
+        if rev.is_some() {
+            anyhow::bail!("Watch mode cannot scan a git revision");
+        }
         let watch_paths: Vec<String> = paths
             .iter()
             .map(|p| p.to_string_lossy().to_string())
             .collect();
         let watcher = codedebt::watch::CodeDebtWatcher::new(scanner, watch_paths);
-        return watcher.watch();
+        watcher.watch()?;
+        return Ok(ExitCode::SUCCESS);
     }
 
-    let start = std::time::Instant::now();
-    let mut all_items = Vec::new();
-
-    // Scan all paths
-    for path in &paths {
-        match scanner.scan(path) {
-            Ok(items) => all_items.extend(items),
-            Err(e) => eprintln!("Error scanning {}: {}", path.display(), e),
-        }
+    if cli.since.is_some() && rev.is_some() {
+        anyhow::bail!("--since compares the working tree and cannot be combined with --rev");
     }
 
+    // Keep suppressed items so they can be counted and audited
+    let scanner = scanner.with_suppressed(true);
+
+    let start = std::time::Instant::now();
+    let (mut report, scan_errors) = scan_all(&scanner, &paths, stdin.as_deref(), rev.as_deref());
     let duration = start.elapsed();
 
+    let (suppressed, all_items): (Vec<_>, Vec<_>) = std::mem::take(&mut report.items)
+        .into_iter()
+        .partition(|item| item.suppressed);
+    let suppressed = scanner.filter_by_severity(&suppressed, severity.clone());
+
     // Apply filters
-    let mut filtered_items = scanner.filter_by_severity(&all_items, cli.severity.into());
+    let mut filtered_items = scanner.filter_by_severity(&all_items, severity.clone());
 
     // Apply age filter if specified
     if let Some(max_age) = cli.max_age {
-        if !cli.git_blame {
+        if !git_blame {
             eprintln!("Warning: --max-age requires --git-blame to be enabled");
         } else {
             filtered_items = scanner.filter_by_age(&filtered_items, max_age);
         }
     }
 
+    if !cli.blame_status.is_empty() {
+        if !git_blame {
+            eprintln!("Warning: --blame-status requires --git-blame to be enabled");
+        } else {
+            let statuses: Vec<BlameStatus> = cli
+                .blame_status
+                .iter()
+                .cloned()
+                .map(BlameStatus::from)
+                .collect();
+            filtered_items = scanner.filter_by_blame_status(&filtered_items, &statuses);
+        }
+    }
+
     // Apply duplicate filter if specified
     if let Some(min_duplicates) = cli.min_duplicates {
-        if !cli.detect_duplicates {
+        if !detect_duplicates {
             eprintln!("Warning: --min-duplicates requires --detect-duplicates to be enabled");
         } else {
             filtered_items = scanner.find_duplicates(&filtered_items, min_duplicates);
         }
     }
 
+    // Apply annotation filters if specified
+    if let Some(assignee) = &cli.assignee {
+        filtered_items = scanner.filter_by_assignee(&filtered_items, assignee);
+    }
+
+    if let Some(issue) = &cli.issue {
+        filtered_items = scanner.filter_by_issue(&filtered_items, issue);
+    }
+
+    if let Some(due_before) = cli.due_before {
+        filtered_items = scanner.filter_due_before(&filtered_items, due_before);
+    }
+
+    // Only keep items that are not in the baseline
+    let mut baselined = None;
+    if let Some(baseline_path) = &cli.baseline {
+        let baseline = Baseline::load(baseline_path)?;
+        let new_items = baseline.new_items(&filtered_items, &baseline_root(baseline_path));
+        baselined = Some(filtered_items.len() - new_items.len());
+        filtered_items = new_items;
+    }
+
+    // Only keep items on lines changed since the base revision
+    let mut debt_diff = None;
+    if let Some(since) = &cli.since {
+        let mut diff = scanner.diff_since(&filtered_items, scan_root(&paths), since)?;
+        diff.resolved = scanner.filter_by_severity(&diff.resolved, severity);
+        filtered_items = diff.added.clone();
+        debt_diff = Some(diff);
+    }
+
     // Handle interactive mode
     if cli.interactive {
         let mut interactive = codedebt::interactive::InteractiveMode::new(filtered_items);
-        return interactive.run();
+        interactive.run()?;
+        return Ok(ExitCode::SUCCESS);
     }
 
-    match cli.format {
+    match format {
         OutputFormat::Pretty => {
-            if cli.summary {
-                print_summary(&scanner, &filtered_items);
+            if summary {
+                print_summary(&scanner, &filtered_items, suppressed.len());
             } else {
                 print_pretty(&filtered_items);
+                if report_suppressed {
+                    print_suppressed(&suppressed);
+                } else if !suppressed.is_empty() {
+                    println!(
+                        "\n{} {} suppressed items (use --report-suppressed to list them)",
+                        "🔇".dimmed(),
+                        suppressed.len().to_string().yellow()
+                    );
+                }
+            }
+
+            if let Some(baselined) = baselined {
+                println!(
+                    "\n{} {} new items, {} already in baseline",
+                    "📏".cyan(),
+                    filtered_items.len().to_string().yellow(),
+                    baselined
+                );
+            }
+
+            if let Some(diff) = &debt_diff {
+                if cli.show_resolved {
+                    print_resolved(&diff.resolved);
+                }
+                println!(
+                    "\n{} Since {}: {} added, {} resolved",
+                    "🔀".cyan(),
+                    &diff.base_commit[..7],
+                    diff.added.len().to_string().yellow(),
+                    diff.resolved.len().to_string().green()
+                );
             }
 
             // Show additional information if requested
-            if cli.file_types {
+            if file_types {
                 print_file_type_distribution(&scanner, &all_items);
             }
 
-            if cli.age_distribution {
-                if !cli.git_blame {
+            if age_distribution {
+                if !git_blame {
                     eprintln!("Warning: --age-distribution requires --git-blame to be enabled");
                 } else {
                     print_age_distribution(&scanner, &all_items);
                 }
             }
 
+            print_skipped(&report.files_skipped);
             println!(
-                "\n{} Scanned {} in {:.2}ms",
+                "\n{} Scanned {} files ({}) in {} in {:.2}ms",
                 "⚡".bright_yellow(),
-                format_paths(&paths),
+                report.files_scanned,
+                indicatif::HumanBytes(report.bytes_read),
+                format_paths(&paths, stdin.as_deref()),
                 duration.as_secs_f64() * 1000.0
             );
         }
         OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&filtered_items)?);
+            report.items = with_suppressed(&filtered_items, &suppressed, report_suppressed);
+            report.duration_ms = duration.as_millis() as u64;
+            let output = JsonOutput {
+                report: &report,
+                suppressed: suppressed.len(),
+                base_commit: debt_diff.as_ref().map(|diff| diff.base_commit.as_str()),
+                resolved: debt_diff
+                    .as_ref()
+                    .filter(|_| cli.show_resolved)
+                    .map(|diff| diff.resolved.as_slice()),
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
         }
         OutputFormat::Csv => {
-            print_csv(&filtered_items);
+            print_csv(&with_suppressed(
+                &filtered_items,
+                &suppressed,
+                report_suppressed,
+            ));
+        }
+        OutputFormat::Sarif => {
+            let items = with_suppressed(&filtered_items, &suppressed, report_suppressed);
+            let log = codedebt::sarif::to_sarif(&items, scanner.patterns());
+            println!("{}", serde_json::to_string_pretty(&log)?);
         }
     }
 
-    Ok(())
+    if scan_errors > 0 {
+        return Ok(ExitCode::from(EXIT_SCAN_ERROR));
+    }
+
+    let violations = thresholds.evaluate(&filtered_items);
+    for violation in &violations {
+        eprintln!("{} Threshold exceeded: {}", "❌".red(), violation);
+    }
+
+    if !violations.is_empty() || (baselined.is_some() && !filtered_items.is_empty()) {
+        return Ok(ExitCode::from(EXIT_THRESHOLD_EXCEEDED));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The scan report printed by `--format json`, with the range and the
+/// resolved items when comparing against `--since`.
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    #[serde(flatten)]
+    report: &'a ScanReport,
+    /// Items silenced by suppression directives, whether or not they are
+    /// listed with `--report-suppressed`
+    suppressed: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base_commit: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resolved: Option<&'a [CodeDebtItem]>,
+}
+
+/// Reported items, followed by the suppressed ones when auditing them.
+fn with_suppressed(
+    items: &[CodeDebtItem],
+    suppressed: &[CodeDebtItem],
+    report_suppressed: bool,
+) -> Vec<CodeDebtItem> {
+    let mut all = items.to_vec();
+    if report_suppressed {
+        all.extend_from_slice(suppressed);
+    }
+    all
+}
+
+/// Parse `--now` as an RFC 3339 timestamp or a date at midnight UTC.
+fn parse_reference_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_time(NaiveTime::MIN).and_utc())
+        .map_err(|_| format!("invalid time '{}', expected RFC 3339 or YYYY-MM-DD", value))
 }
 
 fn resolve_paths(pattern: &str) -> anyhow::Result<Vec<PathBuf>> {
     // Check if it's a glob pattern
     if pattern.contains('*') || pattern.contains('?') || pattern.contains('[') {
-        let mut paths = HashSet::new();
-        for path in glob(pattern)?.flatten() {
-            if path.is_dir() {
-                paths.insert(path);
-            } else if path.is_file() {
-                // For files, add their parent directory
-                if let Some(parent) = path.parent() {
-                    paths.insert(parent.to_path_buf());
-                }
-            }
-        }
-        Ok(paths.into_iter().collect())
+        Ok(glob(pattern)?
+            .flatten()
+            .filter(|path| path.is_dir() || path.is_file())
+            .collect())
     } else {
         // Regular path
         let path = PathBuf::from(pattern);
-        if path.exists() && path.is_dir() {
+        if path.is_dir() || path.is_file() {
             Ok(vec![path])
-        } else if path.exists() && path.is_file() {
-            // For a single file, scan its parent directory
-            if let Some(parent) = path.parent() {
-                Ok(vec![parent.to_path_buf()])
-            } else {
-                Ok(vec![])
-            }
         } else {
             Ok(vec![])
         }
     }
 }
 
-fn format_paths(paths: &[PathBuf]) -> String {
-    if paths.len() == 1 {
-        paths[0].display().to_string()
-    } else {
-        format!("{} directories", paths.len())
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Drop paths given more than once, under any spelling, and directories
+/// inside another directory that is walked anyway.
+fn dedup_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let canonical_paths: Vec<PathBuf> = paths.iter().map(|path| canonical(path)).collect();
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .zip(&canonical_paths)
+        .filter(|(path, resolved)| {
+            let nested = path.is_dir()
+                && canonical_paths.iter().any(|other| {
+                    other != *resolved && other.is_dir() && resolved.starts_with(other)
+                });
+            !nested && seen.insert((*resolved).clone())
+        })
+        .map(|(path, _)| path)
+        .collect()
+}
+
+fn format_paths(paths: &[PathBuf], stdin: Option<&Path>) -> String {
+    match (paths, stdin) {
+        ([path], None) => path.display().to_string(),
+        ([], Some(name)) => format!("{} (stdin)", name.display()),
+        _ => format!("{} paths", paths.len() + usize::from(stdin.is_some())),
+    }
+}
+
+/// Directory that diff mode compares against: the first path, or the
+/// directory containing it if it is a file.
+fn scan_root(paths: &[PathBuf]) -> PathBuf {
+    match paths.first() {
+        Some(path) if path.is_file() => match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        },
+        Some(path) => path.clone(),
+        None => PathBuf::from("."),
     }
 }
 
@@ -329,6 +1202,26 @@ fn print_pretty(items: &[codedebt::CodeDebtItem]) {
             details.push(format!("📅 {}", age_str.dimmed()));
         }
 
+        match item.blame_status {
+            Some(BlameStatus::Uncommitted) => {
+                details.push(format!("✏️  {}", "uncommitted".yellow()))
+            }
+            Some(BlameStatus::Untracked) => details.push(format!("🆕 {}", "untracked".yellow())),
+            _ => {}
+        }
+
+        if let Some(assignee) = &item.assignee {
+            details.push(format!("🙋 {}", assignee.dimmed()));
+        }
+
+        if !item.issue_refs.is_empty() {
+            details.push(format!("🔗 {}", item.issue_refs.join(", ").dimmed()));
+        }
+
+        if let Some(due_date) = item.due_date {
+            details.push(format!("⏰ due {}", due_date.to_string().dimmed()));
+        }
+
         if item.duplicate_count > 1 {
             details.push(format!(
                 "🔄 {} duplicates",
@@ -342,7 +1235,59 @@ fn print_pretty(items: &[codedebt::CodeDebtItem]) {
     }
 }
 
-fn print_summary(scanner: &CodeDebtScanner, items: &[codedebt::CodeDebtItem]) {
+fn print_skipped(skipped: &[SkippedFile]) {
+    if skipped.is_empty() {
+        return;
+    }
+    println!(
+        "\n{} {} files skipped:",
+        "⚠️".yellow(),
+        skipped.len().to_string().yellow()
+    );
+    for file in skipped {
+        println!(
+            "   {} {}",
+            file.path.display().to_string().cyan(),
+            format!("({})", file.reason).dimmed()
+        );
+    }
+}
+
+fn print_resolved(items: &[codedebt::CodeDebtItem]) {
+    if items.is_empty() {
+        return;
+    }
+
+    println!("\n{} {} items resolved:\n", "✅".green(), items.len());
+    for item in items {
+        println!(
+            "  {} {}:{} {}",
+            item.pattern_type.purple().bold(),
+            item.file_path.display().to_string().cyan(),
+            item.line_number.to_string().green(),
+            item.line_content.trim().strikethrough()
+        );
+    }
+}
+
+fn print_suppressed(items: &[codedebt::CodeDebtItem]) {
+    if items.is_empty() {
+        return;
+    }
+
+    println!("\n{} {} items suppressed:\n", "🔇".dimmed(), items.len());
+    for item in items {
+        println!(
+            "  {} {}:{} {}",
+            item.pattern_type.purple().bold(),
+            item.file_path.display().to_string().cyan(),
+            item.line_number.to_string().green(),
+            item.line_content.trim().dimmed()
+        );
+    }
+}
+
+fn print_summary(scanner: &CodeDebtScanner, items: &[codedebt::CodeDebtItem], suppressed: usize) {
     let summary = scanner.get_summary(items);
 
     println!("{} Code Debt Summary:", "📊".cyan());
@@ -360,13 +1305,20 @@ fn print_summary(scanner: &CodeDebtScanner, items: &[codedebt::CodeDebtItem]) {
         "TOTAL".bold(),
         total.to_string().bold().yellow()
     );
+    if suppressed > 0 {
+        println!(
+            "{:15} {:>5}",
+            "SUPPRESSED".dimmed(),
+            suppressed.to_string().dimmed()
+        );
+    }
 }
 
 fn print_csv(items: &[codedebt::CodeDebtItem]) {
-    println!("file_path,line_number,column,severity,pattern_type,line_content,author,age_days,duplicate_count");
+    println!("file_path,line_number,column,severity,pattern_type,line_content,author,age_days,duplicate_count,assignee,issue_refs,due_date,message,suppressed,blame_status,fingerprint,end_column,byte_offset,matched_text");
     for item in items {
         println!(
-            "{},{},{},{:?},{},\"{}\",\"{}\",{},{}",
+            "{},{},{},{:?},{},\"{}\",\"{}\",{},{},\"{}\",\"{}\",{},\"{}\",{},{},{},{},{},\"{}\"",
             item.file_path.display(),
             item.line_number,
             item.column,
@@ -375,7 +1327,17 @@ fn print_csv(items: &[codedebt::CodeDebtItem]) {
             item.line_content.replace('"', "\"\""),
             item.author.as_deref().unwrap_or(""),
             item.age_days.unwrap_or(-1),
-            item.duplicate_count
+            item.duplicate_count,
+            item.assignee.as_deref().unwrap_or("").replace('"', "\"\""),
+            item.issue_refs.join(";"),
+            item.due_date.map(|d| d.to_string()).unwrap_or_default(),
+            item.message.as_deref().unwrap_or("").replace('"', "\"\""),
+            item.suppressed,
+            item.blame_status.map(|s| s.as_str()).unwrap_or(""),
+            item.fingerprint,
+            item.end_column,
+            item.byte_offset,
+            item.matched_text.replace('"', "\"\"")
         );
     }
 }
@@ -401,6 +1363,242 @@ fn print_file_type_distribution(scanner: &CodeDebtScanner, items: &[codedebt::Co
     }
 }
 
+const SEVERITIES: [Severity; 4] = [
+    Severity::Critical,
+    Severity::High,
+    Severity::Medium,
+    Severity::Low,
+];
+
+fn print_history_table(points: &[HistoryPoint]) {
+    if points.is_empty() {
+        println!("{}", "No commits to count".yellow());
+        return;
+    }
+
+    println!("{} Debt history:", "📈".cyan().bold());
+    println!("{}", "═".repeat(72).dimmed());
+    println!(
+        "{:<10}  {:<8}  {:<14}  {:>6}  {:>8}  {:>6}  {:>6}  {:>6}",
+        "DATE", "COMMIT", "TAG", "TOTAL", "CRITICAL", "HIGH", "MEDIUM", "LOW"
+    );
+    let mut previous: Option<usize> = None;
+    for point in points {
+        let count = |severity: &Severity| point.by_severity.get(severity).copied().unwrap_or(0);
+        let trend = match previous {
+            Some(prev) if point.total > prev => format!("▲{}", point.total - prev).red(),
+            Some(prev) if point.total < prev => format!("▼{}", prev - point.total).green(),
+            _ => "".normal(),
+        };
+        println!(
+            "{:<10}  {:<8}  {:<14}  {:>6}  {:>8}  {:>6}  {:>6}  {:>6}  {}",
+            point.date.format("%Y-%m-%d"),
+            &point.commit[..point.commit.len().min(8)],
+            point.tag.as_deref().unwrap_or(""),
+            point.total.to_string().bold(),
+            count(&Severity::Critical),
+            count(&Severity::High),
+            count(&Severity::Medium),
+            count(&Severity::Low),
+            trend
+        );
+        previous = Some(point.total);
+    }
+}
+
+fn print_history_csv(points: &[HistoryPoint]) {
+    let patterns: BTreeSet<&String> = points
+        .iter()
+        .flat_map(|point| point.by_pattern.keys())
+        .collect();
+
+    let mut header = vec!["date", "commit", "tag", "total"];
+    header.extend(["critical", "high", "medium", "low"]);
+    header.extend(patterns.iter().map(|p| p.as_str()));
+    println!("{}", header.join(","));
+
+    for point in points {
+        let mut row = vec![
+            point.date.to_rfc3339(),
+            point.commit.clone(),
+            point.tag.clone().unwrap_or_default(),
+            point.total.to_string(),
+        ];
+        row.extend(SEVERITIES.iter().map(|severity| {
+            point
+                .by_severity
+                .get(severity)
+                .copied()
+                .unwrap_or(0)
+                .to_string()
+        }));
+        row.extend(patterns.iter().map(|pattern| {
+            point
+                .by_pattern
+                .get(*pattern)
+                .copied()
+                .unwrap_or(0)
+                .to_string()
+        }));
+        println!("{}", row.join(","));
+    }
+}
+
+fn print_resolution_table(report: &ResolutionReport, since: &str) {
+    if report.items.is_empty() {
+        println!("{} No debt resolved since {}", "🤷".yellow(), since);
+        return;
+    }
+
+    println!(
+        "{} {} debt items resolved since {}:\n",
+        "🎉".green().bold(),
+        report.items.len(),
+        since
+    );
+    for resolved in &report.items {
+        let item = &resolved.item;
+        println!(
+            "✅ {} {}:{} {}",
+            item.pattern_type.purple(),
+            item.file_path.display().to_string().cyan(),
+            item.line_number,
+            item.line_content.trim().dimmed()
+        );
+
+        let mut details = vec![format!(
+            "🧹 {} on {} ({})",
+            resolved.resolved_by.as_deref().unwrap_or("unknown"),
+            resolved.resolved_at.format("%Y-%m-%d"),
+            &resolved.resolved_commit[..resolved.resolved_commit.len().min(8)]
+        )];
+        if let Some(days) = resolved.days_to_resolve {
+            details.push(format!("⏱️  open {} days", days));
+        }
+        if let Some(author) = &item.author {
+            details.push(format!("👤 added by {}", author));
+        }
+        println!("    {}", details.join(" • ").dimmed());
+    }
+
+    for (title, stats) in [
+        ("Time to resolve by pattern", &report.by_pattern),
+        ("Time to resolve by author", &report.by_author),
+    ] {
+        println!("\n{} {}:", "📊".cyan().bold(), title);
+        println!("{}", "═".repeat(56).dimmed());
+        println!(
+            "{:<24}  {:>8}  {:>8}  {:>8}",
+            "", "RESOLVED", "MEDIAN", "MEAN"
+        );
+        for (key, stats) in stats {
+            let days = |value: Option<f64>| value.map(|d| format!("{:.1}d", d)).unwrap_or_default();
+            println!(
+                "{:<24}  {:>8}  {:>8}  {:>8}",
+                key,
+                stats.resolved,
+                days(stats.median_days),
+                days(stats.mean_days)
+            );
+        }
+    }
+}
+
+fn print_resolution_csv(report: &ResolutionReport) {
+    println!("file_path,line_number,pattern_type,severity,line_content,added_by,added_commit,added_at,resolved_by,resolved_commit,resolved_at,days_to_resolve");
+    for resolved in &report.items {
+        let item = &resolved.item;
+        println!(
+            "{},{},{},{:?},\"{}\",\"{}\",{},{},\"{}\",{},{},{}",
+            item.file_path.display(),
+            item.line_number,
+            item.pattern_type,
+            item.severity,
+            item.line_content.replace('"', "\"\""),
+            item.author.as_deref().unwrap_or("").replace('"', "\"\""),
+            item.commit_hash.as_deref().unwrap_or(""),
+            item.created_at.map(|d| d.to_rfc3339()).unwrap_or_default(),
+            resolved
+                .resolved_by
+                .as_deref()
+                .unwrap_or("")
+                .replace('"', "\"\""),
+            resolved.resolved_commit,
+            resolved.resolved_at.to_rfc3339(),
+            resolved
+                .days_to_resolve
+                .map(|d| d.to_string())
+                .unwrap_or_default()
+        );
+    }
+}
+
+fn print_hotspot_table(hotspots: &[Hotspot], days: i64) {
+    if hotspots.is_empty() {
+        println!(
+            "{} No files with debt changed in the last {} days",
+            "🤷".yellow(),
+            days
+        );
+        return;
+    }
+
+    println!(
+        "{} Debt hotspots over the last {} days:",
+        "🔥".red().bold(),
+        days
+    );
+    println!("{}", "═".repeat(80).dimmed());
+    println!(
+        "{:>6}  {:>5}  {:>5}  {:>7}  {:>7}  {:>5}  FILE",
+        "SCORE", "ITEMS", "DEBT", "COMMITS", "LINES", "CRIT"
+    );
+    for hotspot in hotspots {
+        let critical = hotspot
+            .by_severity
+            .get(&Severity::Critical)
+            .copied()
+            .unwrap_or(0);
+        println!(
+            "{:>6}  {:>5}  {:>5}  {:>7}  {:>7}  {:>5}  {}",
+            hotspot.score.to_string().bold(),
+            hotspot.items,
+            hotspot.debt_score,
+            hotspot.churn.commits,
+            hotspot.churn.lines_changed(),
+            critical,
+            hotspot.file_path.display().to_string().cyan()
+        );
+    }
+}
+
+fn print_hotspot_csv(hotspots: &[Hotspot]) {
+    println!("file_path,score,items,debt_score,commits,lines_added,lines_removed,critical,high,medium,low");
+    for hotspot in hotspots {
+        let mut row = vec![
+            format!(
+                "\"{}\"",
+                hotspot.file_path.display().to_string().replace('"', "\"\"")
+            ),
+            hotspot.score.to_string(),
+            hotspot.items.to_string(),
+            hotspot.debt_score.to_string(),
+            hotspot.churn.commits.to_string(),
+            hotspot.churn.lines_added.to_string(),
+            hotspot.churn.lines_removed.to_string(),
+        ];
+        row.extend(SEVERITIES.iter().map(|severity| {
+            hotspot
+                .by_severity
+                .get(severity)
+                .copied()
+                .unwrap_or(0)
+                .to_string()
+        }));
+        println!("{}", row.join(","));
+    }
+}
+
 fn print_age_distribution(scanner: &CodeDebtScanner, items: &[codedebt::CodeDebtItem]) {
     let distribution = scanner.get_age_distribution(items);
     if distribution.is_empty() {
@@ -412,6 +1610,8 @@ fn print_age_distribution(scanner: &CodeDebtScanner, items: &[codedebt::CodeDebt
 
     // Define order for age buckets
     let order = [
+        "Uncommitted",
+        "Untracked",
         "This week",
         "This month",
         "Last 3 months",
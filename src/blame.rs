@@ -0,0 +1,805 @@
+use anyhow::Result;
+use git2::{BlameOptions, DiffOptions, Oid, Patch, Repository};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the cached format or the way blame is computed changes.
+const CACHE_VERSION: u32 = 4;
+
+/// Ignore-revs file picked up from the repository root, as used by GitHub.
+pub const IGNORE_REVS_FILE_NAME: &str = ".git-blame-ignore-revs";
+
+/// How far a line is followed through ignored commits and moves.
+const MAX_HOPS: usize = 16;
+
+/// Shorter lines, like a lone `}`, are too common to be tracked as moved.
+const MIN_MOVED_LINE_LEN: usize = 8;
+
+/// A line only counts as moved along with its neighbours, so a fresh line
+/// that happens to equal one deleted elsewhere keeps its own commit.
+const MIN_MOVED_BLOCK_LINES: usize = 2;
+
+/// Options that make blame look past commits that only moved or reformatted
+/// lines.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlameConfig {
+    /// Commits to look through, like `git blame --ignore-rev`
+    #[serde(default)]
+    pub ignore_revs: Vec<String>,
+    /// File listing commits to look through, relative to the repository
+    /// root. Defaults to `blame.ignoreRevsFile` from git config, then to
+    /// `.git-blame-ignore-revs` if it exists.
+    pub ignore_revs_file: Option<PathBuf>,
+    /// Ignore whitespace-only changes
+    #[serde(default)]
+    pub ignore_whitespace: bool,
+    /// Follow lines moved or copied from another place in the same commit
+    #[serde(default)]
+    pub track_moves: bool,
+    /// Which date of the blamed commit ages are measured from
+    #[serde(default)]
+    pub age_from: AgeFrom,
+}
+
+/// The date of a commit that a line's age is measured from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgeFrom {
+    /// When the commit was made, which changes on rebase or cherry-pick
+    #[default]
+    Commit,
+    /// When the change was originally written
+    Author,
+}
+
+/// A [`BlameConfig`] resolved against one repository.
+#[derive(Debug, Clone, Default)]
+pub struct BlameSettings {
+    pub ignore_revs: BTreeSet<Oid>,
+    pub ignore_whitespace: bool,
+    pub track_moves: bool,
+}
+
+impl BlameSettings {
+    pub fn resolve(repo: &Repository, config: &BlameConfig) -> Self {
+        let mut revs = config.ignore_revs.clone();
+        if let Some(file) = Self::ignore_revs_file(repo, config) {
+            match std::fs::read_to_string(&file) {
+                Ok(content) => revs.extend(
+                    content
+                        .lines()
+                        .map(|line| line.split('#').next().unwrap_or("").trim())
+                        .filter(|line| !line.is_empty())
+                        .map(String::from),
+                ),
+                Err(e) => debug!("Failed to read {} - {}", file.display(), e),
+            }
+        }
+
+        let ignore_revs = revs
+            .iter()
+            .filter_map(|rev| {
+                let commit = repo.revparse_single(rev).and_then(|o| o.peel_to_commit());
+                if commit.is_err() {
+                    debug!("Ignoring unknown blame ignore rev '{}'", rev);
+                }
+                commit.ok().map(|commit| commit.id())
+            })
+            .collect();
+
+        Self {
+            ignore_revs,
+            ignore_whitespace: config.ignore_whitespace,
+            track_moves: config.track_moves,
+        }
+    }
+
+    fn ignore_revs_file(repo: &Repository, config: &BlameConfig) -> Option<PathBuf> {
+        let workdir = repo.workdir()?;
+        if let Some(file) = &config.ignore_revs_file {
+            return Some(workdir.join(file));
+        }
+        if let Ok(file) = repo
+            .config()
+            .and_then(|c| c.get_path("blame.ignoreRevsFile"))
+        {
+            return Some(workdir.join(file));
+        }
+        Some(workdir.join(IGNORE_REVS_FILE_NAME)).filter(|file| file.is_file())
+    }
+
+    /// Identifies the settings in cache keys.
+    fn fingerprint(&self) -> String {
+        let revs: Vec<String> = self.ignore_revs.iter().map(Oid::to_string).collect();
+        format!(
+            "{}:{}:{}",
+            revs.join(","),
+            self.ignore_whitespace,
+            self.track_moves
+        )
+    }
+}
+
+/// A run of consecutive lines last changed by the same commit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlameHunk {
+    /// First line of the hunk, 1-based
+    pub start_line: usize,
+    pub lines: usize,
+    pub commit: String,
+    pub author: Option<String>,
+    /// Commit time in seconds since the epoch
+    pub time: i64,
+    /// Author time in seconds since the epoch
+    pub author_time: i64,
+}
+
+impl BlameHunk {
+    pub fn time(&self, age_from: AgeFrom) -> i64 {
+        match age_from {
+            AgeFrom::Commit => self.time,
+            AgeFrom::Author => self.author_time,
+        }
+    }
+}
+
+/// Blame of one committed file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileBlame {
+    pub hunks: Vec<BlameHunk>,
+}
+
+impl FileBlame {
+    /// Blame `relative_path` as of HEAD.
+    pub fn compute(
+        repo: &Repository,
+        relative_path: &Path,
+        settings: &BlameSettings,
+    ) -> Result<Self> {
+        let head = repo.head()?.peel_to_commit()?;
+        Self::compute_at(repo, head.id(), relative_path, settings)
+    }
+
+    /// Blame `relative_path` as of `commit`.
+    pub fn compute_at(
+        repo: &Repository,
+        commit: Oid,
+        relative_path: &Path,
+        settings: &BlameSettings,
+    ) -> Result<Self> {
+        let commit = repo.find_commit(commit)?;
+        let mut blamer = Blamer::new(repo, settings);
+        let raw = blamer.blame_at(commit.id(), relative_path)?.to_vec();
+
+        if settings.ignore_revs.is_empty() && !settings.track_moves {
+            let hunks = raw.iter().map(RawHunk::to_hunk).collect();
+            return Ok(Self { hunks });
+        }
+
+        // Follow each line back through ignored commits and moves, then
+        // merge runs of lines that end up with the same commit
+        let blob = commit
+            .tree()?
+            .get_path(relative_path)?
+            .to_object(repo)?
+            .peel_to_blob()?;
+        let content = String::from_utf8_lossy(blob.content()).into_owned();
+        let lines: Vec<&str> = content.lines().collect();
+
+        let mut hunks: Vec<BlameHunk> = Vec::new();
+        for hunk in &raw {
+            for offset in 0..hunk.lines {
+                let line_number = hunk.final_start + offset;
+                let text = lines.get(line_number - 1).copied().unwrap_or("");
+                let origin = blamer.attribute(hunk, offset, text);
+                match hunks.last_mut() {
+                    Some(last)
+                        if last.commit == origin.commit.to_string()
+                            && last.start_line + last.lines == line_number =>
+                    {
+                        last.lines += 1;
+                    }
+                    _ => hunks.push(BlameHunk {
+                        start_line: line_number,
+                        lines: 1,
+                        ..origin.to_hunk()
+                    }),
+                }
+            }
+        }
+        Ok(Self { hunks })
+    }
+
+    pub fn line(&self, line_number: usize) -> Option<&BlameHunk> {
+        let idx = self
+            .hunks
+            .partition_point(|hunk| hunk.start_line + hunk.lines <= line_number);
+        self.hunks
+            .get(idx)
+            .filter(|hunk| hunk.start_line <= line_number)
+    }
+}
+
+/// A libgit2 blame hunk with the position of its lines in the blamed commit.
+#[derive(Debug, Clone)]
+struct RawHunk {
+    final_start: usize,
+    lines: usize,
+    commit: Oid,
+    orig_path: PathBuf,
+    orig_start: usize,
+    author: Option<String>,
+    time: i64,
+    author_time: i64,
+}
+
+impl RawHunk {
+    fn to_hunk(&self) -> BlameHunk {
+        BlameHunk {
+            start_line: self.final_start,
+            lines: self.lines,
+            commit: self.commit.to_string(),
+            author: self.author.clone(),
+            time: self.time,
+            author_time: self.author_time,
+        }
+    }
+}
+
+/// Where a line on the new side of a patch was on the old side.
+pub(crate) enum OldLine {
+    Unchanged(usize),
+    /// The line was changed; the old line at the same offset in the hunk,
+    /// if the hunk replaced rather than only added lines
+    Changed(Option<usize>),
+}
+
+/// Map `line` on the new side of a patch made with no context lines.
+pub(crate) fn old_line(patch: &Patch, line: usize) -> Option<OldLine> {
+    // Zero-length ranges are reported as starting at the line before
+    let end = |start: u32, len: u32| (start + len.max(1)) as usize;
+    let mut mapped = line;
+    for idx in 0..patch.num_hunks() {
+        let (hunk, _) = patch.hunk(idx).ok()?;
+        let new_start = hunk.new_start() as usize;
+        if hunk.new_lines() > 0 && line >= new_start && line < new_start + hunk.new_lines() as usize
+        {
+            if hunk.old_lines() == 0 {
+                return Some(OldLine::Changed(None));
+            }
+            let within = (line - new_start).min(hunk.old_lines() as usize - 1);
+            return Some(OldLine::Changed(Some(hunk.old_start() as usize + within)));
+        }
+        let new_end = end(hunk.new_start(), hunk.new_lines());
+        if line < new_end {
+            break;
+        }
+        mapped = line - new_end + end(hunk.old_start(), hunk.old_lines());
+    }
+    Some(OldLine::Unchanged(mapped))
+}
+
+/// A run of consecutive lines removed from or added to one file.
+#[derive(Debug, Default)]
+struct LineBlock {
+    path: PathBuf,
+    /// First line of the block, 1-based
+    start: usize,
+    /// Trimmed content of each line
+    lines: Vec<String>,
+}
+
+impl LineBlock {
+    /// Extend the last block if `line` follows it, or start a new one.
+    fn push(blocks: &mut Vec<LineBlock>, path: &Path, line: usize, text: String) {
+        match blocks.last_mut() {
+            Some(last) if last.path == path && last.start + last.lines.len() == line => {
+                last.lines.push(text);
+            }
+            _ => blocks.push(LineBlock {
+                path: path.to_path_buf(),
+                start: line,
+                lines: vec![text],
+            }),
+        }
+    }
+}
+
+/// Lines removed and added by a commit relative to its first parent.
+#[derive(Debug, Default)]
+struct CommitChanges {
+    removed: Vec<LineBlock>,
+    added: Vec<LineBlock>,
+}
+
+/// Blames files at arbitrary commits, remembering results so lines that
+/// need to be followed further back share the work.
+struct Blamer<'r> {
+    repo: &'r Repository,
+    settings: &'r BlameSettings,
+    blames: HashMap<(Oid, PathBuf), Vec<RawHunk>>,
+    changes: HashMap<Oid, CommitChanges>,
+}
+
+impl<'r> Blamer<'r> {
+    fn new(repo: &'r Repository, settings: &'r BlameSettings) -> Self {
+        Self {
+            repo,
+            settings,
+            blames: HashMap::new(),
+            changes: HashMap::new(),
+        }
+    }
+
+    fn blame_at(&mut self, commit: Oid, path: &Path) -> Result<&[RawHunk]> {
+        let key = (commit, path.to_path_buf());
+        if !self.blames.contains_key(&key) {
+            let mut options = BlameOptions::new();
+            options
+                .newest_commit(commit)
+                .ignore_whitespace(self.settings.ignore_whitespace);
+            let blame = self.repo.blame_file(path, Some(&mut options))?;
+            let hunks = blame
+                .iter()
+                .map(|hunk| {
+                    let oid = hunk.final_commit_id();
+                    let author_time = hunk.final_signature().when().seconds();
+                    let time = match self.repo.find_commit(oid) {
+                        Ok(commit) => commit.time().seconds(),
+                        Err(_) => author_time,
+                    };
+                    RawHunk {
+                        final_start: hunk.final_start_line(),
+                        lines: hunk.lines_in_hunk(),
+                        commit: oid,
+                        orig_path: hunk.path().unwrap_or(path).to_path_buf(),
+                        orig_start: hunk.orig_start_line(),
+                        author: hunk.final_signature().name().map(|s| s.to_string()),
+                        time,
+                        author_time,
+                    }
+                })
+                .collect();
+            self.blames.insert(key.clone(), hunks);
+        }
+        Ok(&self.blames[&key])
+    }
+
+    /// The commit a line really comes from, looking through ignored commits
+    /// and, if enabled, moves within a commit.
+    fn attribute(&mut self, hunk: &RawHunk, offset: usize, text: &str) -> RawHunk {
+        let mut current = hunk.clone();
+        let mut path = hunk.orig_path.clone();
+        let mut line = hunk.orig_start + offset;
+
+        for _ in 0..MAX_HOPS {
+            let target = if self.settings.ignore_revs.contains(&current.commit) {
+                self.line_in_parent(current.commit, &path, line)
+            } else if self.settings.track_moves {
+                self.moved_from(current.commit, &path, line, text)
+            } else {
+                None
+            };
+            let Some((parent, parent_path, parent_line)) = target else {
+                break;
+            };
+
+            let found = self.blame_at(parent, &parent_path).ok().and_then(|hunks| {
+                hunks
+                    .iter()
+                    .find(|h| h.final_start <= parent_line && parent_line < h.final_start + h.lines)
+                    .cloned()
+            });
+            let Some(found) = found else {
+                break;
+            };
+            line = found.orig_start + (parent_line - found.final_start);
+            path = found.orig_path.clone();
+            current = found;
+        }
+        current
+    }
+
+    /// Where `line` of `path` in `commit` was in its first parent. Lines the
+    /// commit added from scratch have no previous position.
+    fn line_in_parent(
+        &self,
+        commit: Oid,
+        path: &Path,
+        line: usize,
+    ) -> Option<(Oid, PathBuf, usize)> {
+        let commit = self.repo.find_commit(commit).ok()?;
+        let parent = commit.parent(0).ok()?;
+        let new_blob = self.blob_at(&commit, path)?;
+        let old_blob = self.blob_at(&parent, path)?;
+
+        let mut options = DiffOptions::new();
+        options.context_lines(0);
+        let patch = Patch::from_blobs(
+            &old_blob,
+            Some(path),
+            &new_blob,
+            Some(path),
+            Some(&mut options),
+        )
+        .ok()?;
+
+        match old_line(&patch, line)? {
+            OldLine::Unchanged(old) | OldLine::Changed(Some(old)) => {
+                Some((parent.id(), path.to_path_buf(), old))
+            }
+            OldLine::Changed(None) => None,
+        }
+    }
+
+    /// Where `line` of `path`, added by `commit`, was removed in the same
+    /// commit, if it was moved rather than written.
+    ///
+    /// libgit2 accepts `BlameOptions::track_copies_*` but does not implement
+    /// them, so moves are matched here: the line must have been removed in a
+    /// block of at least [`MIN_MOVED_BLOCK_LINES`] lines that reappears
+    /// around it, and only one such block may exist in the commit.
+    fn moved_from(
+        &mut self,
+        commit: Oid,
+        path: &Path,
+        line: usize,
+        text: &str,
+    ) -> Option<(Oid, PathBuf, usize)> {
+        if text.trim().len() < MIN_MOVED_LINE_LEN {
+            return None;
+        }
+        let commit = self.repo.find_commit(commit).ok()?;
+        let parent = commit.parent(0).ok()?;
+        if !self.changes.contains_key(&commit.id()) {
+            let changes = self.changes_by(&commit, &parent).unwrap_or_default();
+            self.changes.insert(commit.id(), changes);
+        }
+        let changes = &self.changes[&commit.id()];
+
+        let added = changes.added.iter().find(|block| {
+            block.path == path && block.start <= line && line < block.start + block.lines.len()
+        })?;
+        let at = line - added.start;
+
+        let mut candidates = changes.removed.iter().flat_map(|removed| {
+            removed
+                .lines
+                .iter()
+                .enumerate()
+                .filter(|(_, text)| **text == added.lines[at])
+                .filter(move |(idx, _)| {
+                    let before = (1..=at.min(*idx))
+                        .take_while(|k| added.lines[at - k] == removed.lines[idx - k])
+                        .count();
+                    let after = (1..)
+                        .take_while(|k| {
+                            matches!(
+                                (added.lines.get(at + k), removed.lines.get(idx + k)),
+                                (Some(a), Some(r)) if a == r
+                            )
+                        })
+                        .count();
+                    before + after + 1 >= MIN_MOVED_BLOCK_LINES
+                })
+                .map(move |(idx, _)| (removed.path.clone(), removed.start + idx))
+        });
+        let (old_path, old_line) = candidates.next()?;
+        if candidates.next().is_some() {
+            // Ambiguous, e.g. boilerplate removed in several places
+            return None;
+        }
+        Some((parent.id(), old_path, old_line))
+    }
+
+    fn changes_by(&self, commit: &git2::Commit, parent: &git2::Commit) -> Result<CommitChanges> {
+        let mut options = DiffOptions::new();
+        options.context_lines(0);
+        let diff = self.repo.diff_tree_to_tree(
+            Some(&parent.tree()?),
+            Some(&commit.tree()?),
+            Some(&mut options),
+        )?;
+
+        let mut changes = CommitChanges::default();
+        diff.foreach(
+            &mut |_, _| true,
+            None,
+            None,
+            Some(&mut |delta, _, line| {
+                let text = || String::from_utf8_lossy(line.content()).trim().to_string();
+                match (
+                    line.origin(),
+                    delta.old_file().path(),
+                    delta.new_file().path(),
+                ) {
+                    ('-', Some(path), _) => {
+                        if let Some(lineno) = line.old_lineno() {
+                            LineBlock::push(&mut changes.removed, path, lineno as usize, text());
+                        }
+                    }
+                    ('+', _, Some(path)) => {
+                        if let Some(lineno) = line.new_lineno() {
+                            LineBlock::push(&mut changes.added, path, lineno as usize, text());
+                        }
+                    }
+                    _ => {}
+                }
+                true
+            }),
+        )?;
+        Ok(changes)
+    }
+
+    fn blob_at(&self, commit: &git2::Commit, path: &Path) -> Option<git2::Blob<'r>> {
+        let entry = commit.tree().ok()?.get_path(path).ok()?;
+        self.repo.find_blob(entry.id()).ok()
+    }
+}
+
+/// On-disk blame results under the repository's git directory, keyed by the
+/// path and blob id of the committed file and the blame settings, so
+/// unchanged files are never blamed twice.
+pub struct BlameCache {
+    dir: PathBuf,
+}
+
+impl BlameCache {
+    pub fn for_repo(repo: &Repository) -> Self {
+        Self {
+            dir: repo.path().join("codedebt").join("blame"),
+        }
+    }
+
+    pub fn key(relative_path: &Path, blob: Oid, settings: &BlameSettings) -> String {
+        let path = relative_path.to_string_lossy().replace('\\', "/");
+        let mut hasher = Sha256::new();
+        hasher.update(CACHE_VERSION.to_le_bytes());
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+        hasher.update(blob.as_bytes());
+        hasher.update(settings.fingerprint().as_bytes());
+        hasher.finalize()[..16]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<FileBlame> {
+        let content = std::fs::read(self.entry(key)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    /// Store an entry. Failures only cost a re-blame next time, so they are
+    /// logged rather than returned.
+    pub fn put(&self, key: &str, blame: &FileBlame) {
+        let write = || -> Result<()> {
+            std::fs::create_dir_all(&self.dir)?;
+            // Write to a scratch file first so concurrent runs never see
+            // a partial entry
+            let tmp = self.dir.join(format!("{}.{}.tmp", key, std::process::id()));
+            std::fs::write(&tmp, serde_json::to_vec(blame)?)?;
+            std::fs::rename(&tmp, self.entry(key))?;
+            Ok(())
+        };
+        if let Err(e) = write() {
+            debug!("Failed to cache blame in {} - {}", self.dir.display(), e);
+        }
+    }
+
+    fn entry(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::GitAnalyzer;
+    use crate::models::{CodeDebtItem, Severity};
+    use crate::test_support::{commit, now};
+
+    fn hunk(start_line: usize, lines: usize, commit: &str) -> BlameHunk {
+        BlameHunk {
+            start_line,
+            lines,
+            commit: commit.to_string(),
+            author: None,
+            time: 0,
+            author_time: 0,
+        }
+    }
+
+    #[test]
+    fn test_line_lookup() {
+        let blame = FileBlame {
+            hunks: vec![hunk(1, 2, "a"), hunk(3, 1, "b"), hunk(4, 5, "c")],
+        };
+        assert_eq!(blame.line(1).unwrap().commit, "a");
+        assert_eq!(blame.line(2).unwrap().commit, "a");
+        assert_eq!(blame.line(3).unwrap().commit, "b");
+        assert_eq!(blame.line(8).unwrap().commit, "c");
+        assert!(blame.line(9).is_none());
+        assert!(blame.line(0).is_none());
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let cache = BlameCache::for_repo(&repo);
+        let settings = BlameSettings::default();
+        let key = BlameCache::key(Path::new("src/lib.rs"), Oid::zero(), &settings);
+        assert!(cache.get(&key).is_none());
+
+        cache.put(
+            &key,
+            &FileBlame {
+                hunks: vec![hunk(1, 3, "abc")],
+            },
+        );
+        assert_eq!(cache.get(&key).unwrap().line(2).unwrap().commit, "abc");
+        assert_ne!(
+            key,
+            BlameCache::key(Path::new("src/main.rs"), Oid::zero(), &settings)
+        );
+    }
+
+    #[test]
+    fn test_add_git_information_uses_cache() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let file = temp_dir.path().join("lib.rs");
+        commit(
+            &repo,
+            &[(
+                "lib.rs",
+                Some("// TODO: one\nfn main() {}\n// FIXME: two\n"),
+            )],
+            "Alice",
+            now(),
+        );
+
+        let mut items = vec![
+            CodeDebtItem::new(&file, 1, 4, "// TODO: one", "TODO", Severity::Medium),
+            CodeDebtItem::new(&file, 3, 4, "// FIXME: two", "FIXME", Severity::High),
+        ];
+        GitAnalyzer::add_git_information(&mut items, &BlameConfig::default(), chrono::Utc::now());
+        assert!(items
+            .iter()
+            .all(|item| item.author.as_deref() == Some("Alice") && item.age_days == Some(0)));
+
+        let cached = std::fs::read_dir(repo.path().join("codedebt").join("blame"))
+            .unwrap()
+            .count();
+        assert_eq!(cached, 1);
+    }
+
+    fn author_of(repo: &Repository, path: &str, line: usize, config: &BlameConfig) -> String {
+        let settings = BlameSettings::resolve(repo, config);
+        let blame = FileBlame::compute(repo, Path::new(path), &settings).unwrap();
+        blame.line(line).unwrap().author.clone().unwrap()
+    }
+
+    #[test]
+    fn test_ignore_revs_and_whitespace() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit(
+            &repo,
+            &[("lib.rs", Some("fn a() {\n// TODO: handle errors\n}\n"))],
+            "Alice",
+            now(),
+        );
+        let reformat = commit(
+            &repo,
+            &[("lib.rs", Some("fn a() {\n    // TODO: handle errors\n}\n"))],
+            "Formatter",
+            now(),
+        );
+
+        let default = BlameConfig::default();
+        assert_eq!(author_of(&repo, "lib.rs", 2, &default), "Formatter");
+
+        let ignore_rev = BlameConfig {
+            ignore_revs: vec![reformat.to_string()],
+            ..Default::default()
+        };
+        assert_eq!(author_of(&repo, "lib.rs", 2, &ignore_rev), "Alice");
+
+        std::fs::write(
+            temp_dir.path().join(IGNORE_REVS_FILE_NAME),
+            format!("# reformat\n{}\n", reformat),
+        )
+        .unwrap();
+        assert_eq!(author_of(&repo, "lib.rs", 2, &default), "Alice");
+        std::fs::remove_file(temp_dir.path().join(IGNORE_REVS_FILE_NAME)).unwrap();
+
+        let whitespace = BlameConfig {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+        assert_eq!(author_of(&repo, "lib.rs", 2, &whitespace), "Alice");
+    }
+
+    #[test]
+    fn test_track_moves_across_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit(
+            &repo,
+            &[(
+                "old.rs",
+                Some("fn a() {}\n// TODO: handle errors\nfn b() {}\n"),
+            )],
+            "Alice",
+            now(),
+        );
+        commit(
+            &repo,
+            &[
+                ("old.rs", Some("fn a() {}\n")),
+                (
+                    "new.rs",
+                    Some("// header\n// TODO: handle errors\nfn b() {}\n"),
+                ),
+            ],
+            "Bob",
+            now(),
+        );
+
+        assert_eq!(
+            author_of(&repo, "new.rs", 2, &BlameConfig::default()),
+            "Bob"
+        );
+        let track_moves = BlameConfig {
+            track_moves: true,
+            ..Default::default()
+        };
+        assert_eq!(author_of(&repo, "new.rs", 2, &track_moves), "Alice");
+        assert_eq!(author_of(&repo, "new.rs", 1, &track_moves), "Bob");
+    }
+
+    #[test]
+    fn test_track_moves_needs_a_unique_block() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit(
+            &repo,
+            &[
+                ("a.rs", Some("fn a() {}\n// TODO: fix this\n")),
+                ("b.rs", Some("fn x() {}\n// TODO: fix this\nfn y() {}\n")),
+                ("c.rs", Some("fn z() {}\n// TODO: fix this\nfn y() {}\n")),
+            ],
+            "Alice",
+            now(),
+        );
+        commit(
+            &repo,
+            &[
+                ("a.rs", Some("fn a() {}\n")),
+                ("b.rs", Some("fn x() {}\n")),
+                ("c.rs", Some("fn z() {}\n")),
+                (
+                    "new.rs",
+                    Some("fn n() {}\n// TODO: fix this\n\n// TODO: fix this\nfn y() {}\n"),
+                ),
+            ],
+            "Bob",
+            now(),
+        );
+
+        let track_moves = BlameConfig {
+            track_moves: true,
+            ..Default::default()
+        };
+        // Only the line itself matches what was removed from a.rs
+        assert_eq!(author_of(&repo, "new.rs", 2, &track_moves), "Bob");
+        // The block matches both b.rs and c.rs
+        assert_eq!(author_of(&repo, "new.rs", 4, &track_moves), "Bob");
+    }
+}
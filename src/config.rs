@@ -0,0 +1,271 @@
+use crate::blame::BlameConfig;
+use crate::models::Severity;
+use crate::patterns::Pattern;
+use crate::scanner::CodeDebtScanner;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE_NAME: &str = ".codedebt.toml";
+
+/// Project-level settings loaded from a `.codedebt.toml` file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Drop the built-in patterns and only use the ones declared here
+    #[serde(default)]
+    pub replace_default_patterns: bool,
+    #[serde(default)]
+    pub patterns: Vec<PatternConfig>,
+    pub extensions: Option<Vec<String>>,
+    /// Directories to ignore in addition to the built-in list
+    pub ignore_dirs: Option<Vec<String>>,
+    /// Only scan files matching these globs
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Skip files and directories matching these globs
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Only report matches inside comments (on by default)
+    pub comments_only: Option<bool>,
+    /// Report every match on a line instead of the first per pattern
+    pub all_matches: Option<bool>,
+    #[serde(default)]
+    pub blame: BlameConfig,
+    #[serde(default)]
+    pub defaults: Defaults,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatternConfig {
+    pub name: String,
+    pub regex: String,
+    pub severity: Severity,
+}
+
+/// Default values for CLI options. Flags given on the command line win.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Defaults {
+    pub severity: Option<Severity>,
+    pub format: Option<String>,
+    #[serde(default)]
+    pub summary: bool,
+    #[serde(default)]
+    pub git_blame: bool,
+    #[serde(default)]
+    pub detect_duplicates: bool,
+    #[serde(default)]
+    pub file_types: bool,
+    #[serde(default)]
+    pub age_distribution: bool,
+    #[serde(default)]
+    pub progress: bool,
+    #[serde(default)]
+    pub report_suppressed: bool,
+    pub fail_on: Option<Severity>,
+    pub max_items: Option<usize>,
+    /// Per-severity limits, e.g. `max = { critical = 0, high = 10 }`
+    #[serde(default)]
+    pub max: BTreeMap<Severity, usize>,
+}
+
+impl Config {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Look for a config file in `start` and each of its ancestors.
+    pub fn discover<P: AsRef<Path>>(start: P) -> Result<Option<(PathBuf, Self)>> {
+        let start = start.as_ref();
+        let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+        let start = if start.is_file() {
+            start.parent().map(Path::to_path_buf).unwrap_or(start)
+        } else {
+            start
+        };
+
+        for dir in start.ancestors() {
+            let candidate = dir.join(CONFIG_FILE_NAME);
+            if candidate.is_file() {
+                let config = Self::load(&candidate)?;
+                return Ok(Some((candidate, config)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn custom_patterns(&self) -> Result<Vec<Pattern>> {
+        self.patterns
+            .iter()
+            .map(|p| {
+                Pattern::new(p.name.clone(), &p.regex, p.severity.clone())
+                    .with_context(|| format!("Invalid regex for pattern '{}'", p.name))
+            })
+            .collect()
+    }
+
+    /// Apply the file settings to a scanner. Custom patterns replace built-in
+    /// patterns of the same name.
+    pub fn apply(&self, mut scanner: CodeDebtScanner) -> Result<CodeDebtScanner> {
+        let custom = self.custom_patterns()?;
+        if self.replace_default_patterns && custom.is_empty() {
+            anyhow::bail!("replace_default_patterns requires at least one pattern");
+        }
+        if self.replace_default_patterns || !custom.is_empty() {
+            let mut patterns = if self.replace_default_patterns {
+                Vec::new()
+            } else {
+                Pattern::default_patterns()
+                    .into_iter()
+                    .filter(|p| !custom.iter().any(|c| c.name == p.name))
+                    .collect()
+            };
+            patterns.extend(custom);
+            scanner = scanner.with_patterns(patterns);
+        }
+
+        if let Some(extensions) = &self.extensions {
+            scanner = scanner.with_file_extensions(extensions.clone());
+        }
+
+        if let Some(dirs) = &self.ignore_dirs {
+            let mut ignore_dirs = Pattern::default_ignore_dirs();
+            ignore_dirs.extend(dirs.iter().cloned());
+            scanner = scanner.with_ignore_dirs(ignore_dirs);
+        }
+
+        if !self.include.is_empty() {
+            scanner = scanner.with_include(self.include.clone());
+        }
+
+        if !self.exclude.is_empty() {
+            scanner = scanner.with_exclude(self.exclude.clone());
+        }
+
+        scanner = scanner.with_blame_config(self.blame.clone());
+
+        if let Some(comments_only) = self.comments_only {
+            scanner = scanner.with_comments_only(comments_only);
+        }
+
+        if let Some(all_matches) = self.all_matches {
+            scanner = scanner.with_all_matches(all_matches);
+        }
+
+        Ok(scanner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_config() {
+        let config: Config = toml::from_str(
+            r#"
+extensions = ["rs", "py"]
+ignore_dirs = ["generated"]
+comments_only = false
+
+[[patterns]]
+name = "URGENT"
+regex = '(?i)\bURGENT\b'
+severity = "critical"
+
+[defaults]
+severity = "high"
+format = "json"
+git_blame = true
+fail_on = "critical"
+max = { high = 10 }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.patterns.len(), 1);
+        assert_eq!(config.patterns[0].severity, Severity::Critical);
+        assert_eq!(config.defaults.severity, Some(Severity::High));
+        assert_eq!(config.defaults.format.as_deref(), Some("json"));
+        assert!(config.defaults.git_blame);
+        assert!(!config.defaults.detect_duplicates);
+        assert_eq!(config.defaults.fail_on, Some(Severity::Critical));
+        assert_eq!(config.defaults.max.get(&Severity::High), Some(&10));
+
+        let scanner = config.apply(CodeDebtScanner::new()).unwrap();
+        assert!(scanner.patterns().iter().any(|p| p.name == "URGENT"));
+        assert!(scanner.patterns().iter().any(|p| p.name == "TODO"));
+        assert_eq!(scanner.file_extensions, vec!["rs", "py"]);
+        assert!(scanner.ignore_dirs.contains(&"generated".to_string()));
+        assert!(scanner.ignore_dirs.contains(&"node_modules".to_string()));
+        assert!(!scanner.options.comments_only);
+    }
+
+    #[test]
+    fn test_replace_default_patterns() {
+        let config: Config = toml::from_str(
+            r#"
+replace_default_patterns = true
+
+[[patterns]]
+name = "TODO"
+regex = '\bTODO\b'
+severity = "low"
+"#,
+        )
+        .unwrap();
+
+        let scanner = config.apply(CodeDebtScanner::new()).unwrap();
+        assert_eq!(scanner.patterns().len(), 1);
+        assert_eq!(scanner.patterns()[0].severity, Severity::Low);
+    }
+
+    #[test]
+    fn test_replace_default_patterns_without_patterns() {
+        let config: Config = toml::from_str("replace_default_patterns = true\n").unwrap();
+
+        let error = config.apply(CodeDebtScanner::new()).err().unwrap();
+        assert!(error
+            .to_string()
+            .contains("replace_default_patterns requires at least one pattern"));
+    }
+
+    #[test]
+    fn test_invalid_pattern_regex() {
+        let config: Config = toml::from_str(
+            r#"
+[[patterns]]
+name = "BROKEN"
+regex = "(unclosed"
+severity = "low"
+"#,
+        )
+        .unwrap();
+
+        assert!(config.apply(CodeDebtScanner::new()).is_err());
+    }
+
+    #[test]
+    fn test_discover_from_subdirectory() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join(CONFIG_FILE_NAME),
+            "extensions = [\"go\"]\n",
+        )
+        .unwrap();
+        let nested = temp_dir.path().join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let (path, config) = Config::discover(&nested).unwrap().unwrap();
+        assert!(path.ends_with(CONFIG_FILE_NAME));
+        assert_eq!(config.extensions, Some(vec!["go".to_string()]));
+    }
+}
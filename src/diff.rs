@@ -0,0 +1,188 @@
+use crate::git::GitAnalyzer;
+use crate::models::CodeDebtItem;
+use crate::revision::TreeWalker;
+use crate::scanner::{CodeDebtScanner, FileScan};
+use anyhow::{Context, Result};
+use git2::Repository;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Debt introduced and resolved by the changes since a base revision.
+#[derive(Debug, Clone, Serialize)]
+pub struct DebtDiff {
+    pub base_commit: String,
+    pub added: Vec<CodeDebtItem>,
+    pub resolved: Vec<CodeDebtItem>,
+}
+
+impl CodeDebtScanner {
+    /// Compare the working tree under `root` with the merge-base of HEAD and
+    /// `rev`. `items` are the results of scanning the working tree; only those
+    /// on added lines are kept. Resolved items are found by scanning the base
+    /// version of each file with removed lines, if that file is under `root`
+    /// and would have been scanned by the same rules.
+    pub fn diff_since<P: AsRef<Path>>(
+        &self,
+        items: &[CodeDebtItem],
+        root: P,
+        rev: &str,
+    ) -> Result<DebtDiff> {
+        let repo = Repository::discover(root.as_ref())
+            .with_context(|| format!("No git repository found at {}", root.as_ref().display()))?;
+        let workdir = repo
+            .workdir()
+            .context("Diff mode requires a repository with a working directory")?
+            .canonicalize()?;
+        let changes = GitAnalyzer::changes_since(&repo, rev)?;
+        let root_abs = root.as_ref().canonicalize()?;
+
+        let mut relative_paths: HashMap<&Path, Option<PathBuf>> = HashMap::new();
+        let added = items
+            .iter()
+            .filter(|item| {
+                let relative = relative_paths
+                    .entry(item.file_path.as_path())
+                    .or_insert_with(|| {
+                        item.file_path.canonicalize().ok().and_then(|path| {
+                            path.strip_prefix(&workdir).ok().map(Path::to_path_buf)
+                        })
+                    });
+                relative
+                    .as_ref()
+                    .and_then(|path| changes.added.get(path))
+                    .is_some_and(|lines| lines.contains(&item.line_number))
+            })
+            .cloned()
+            .collect();
+
+        let base_tree = repo.find_commit(changes.base)?.tree()?;
+        let prefix = root_abs
+            .strip_prefix(&workdir)
+            .ok()
+            .filter(|prefix| !prefix.as_os_str().is_empty());
+        let base_files: HashMap<PathBuf, git2::Oid> = TreeWalker::new(self)?
+            .files(&repo, &base_tree, prefix)?
+            .into_iter()
+            .map(|file| (file.path, file.blob))
+            .collect();
+        let mut resolved = Vec::new();
+        for (path, lines) in &changes.removed {
+            let Some(bytes) = base_files
+                .get(path)
+                .and_then(|blob| repo.find_blob(*blob).ok())
+                .map(|blob| blob.content().to_vec())
+            else {
+                continue;
+            };
+            // Report resolved items with paths shaped like the scanned ones
+            let absolute = workdir.join(path);
+            let display_path = match absolute.strip_prefix(&root_abs) {
+                Ok(relative) => root.as_ref().join(relative),
+                Err(_) => absolute,
+            };
+            if let FileScan::Scanned { items, .. } =
+                Self::scan_bytes(&display_path, &bytes, &self.matcher, &self.options)
+            {
+                resolved.extend(
+                    items
+                        .into_iter()
+                        .filter(|item| lines.contains(&item.line_number)),
+                );
+            }
+        }
+        self.add_file_extensions(&mut resolved);
+        resolved.sort_by(|a, b| {
+            a.severity
+                .cmp(&b.severity)
+                .then_with(|| a.file_path.cmp(&b.file_path))
+                .then_with(|| a.line_number.cmp(&b.line_number))
+        });
+
+        Ok(DebtDiff {
+            base_commit: changes.base.to_string(),
+            added,
+            resolved,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{commit, now};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_diff_since_reports_added_and_resolved() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit(
+            &repo,
+            &[(
+                "lib.rs",
+                Some("// TODO: old one\n// FIXME: going away\nfn main() {}\n"),
+            )],
+            "Dev",
+            now(),
+        );
+
+        fs::write(
+            temp_dir.path().join("lib.rs"),
+            "// TODO: old one\nfn main() {}\n// HACK: new\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("new.py"), "# TODO: untracked\n").unwrap();
+
+        let scanner = CodeDebtScanner::new();
+        let items = scanner.scan(temp_dir.path()).unwrap();
+        assert_eq!(items.len(), 3);
+
+        let diff = scanner.diff_since(&items, temp_dir.path(), "HEAD").unwrap();
+        let mut added: Vec<_> = diff.added.iter().map(|i| i.pattern_type.as_str()).collect();
+        added.sort();
+        assert_eq!(added, vec!["HACK", "TODO"]);
+        assert!(diff
+            .added
+            .iter()
+            .all(|i| i.line_content != "// TODO: old one"));
+
+        assert_eq!(diff.resolved.len(), 1);
+        assert_eq!(diff.resolved[0].pattern_type, "FIXME");
+        assert_eq!(diff.resolved[0].line_number, 2);
+    }
+
+    #[test]
+    fn test_diff_since_ignores_unscanned_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit(
+            &repo,
+            &[
+                ("README.md", Some("TODO: docs\n")),
+                ("node_modules/x.js", Some("// TODO: dep\n")),
+                ("lib.rs", Some("// TODO: outside root\n")),
+                ("src/main.rs", Some("// FIXME: gone\n")),
+            ],
+            "Dev",
+            now(),
+        );
+
+        for path in ["README.md", "node_modules/x.js", "lib.rs", "src/main.rs"] {
+            fs::write(temp_dir.path().join(path), "\n").unwrap();
+        }
+
+        let scanner = CodeDebtScanner::new();
+        let diff = scanner.diff_since(&[], temp_dir.path(), "HEAD").unwrap();
+        let resolved: Vec<_> = diff.resolved.iter().map(|i| &i.file_path).collect();
+        assert_eq!(resolved.len(), 2);
+        assert!(resolved.iter().any(|p| p.ends_with("lib.rs")));
+        assert!(resolved.iter().any(|p| p.ends_with("src/main.rs")));
+
+        let root = temp_dir.path().join("src");
+        let diff = scanner.diff_since(&[], &root, "HEAD").unwrap();
+        assert_eq!(diff.resolved.len(), 1);
+        assert_eq!(diff.resolved[0].file_path, root.join("main.rs"));
+    }
+}
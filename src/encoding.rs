@@ -0,0 +1,324 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+
+/// How many leading bytes are sniffed for NULs and UTF-16 without a BOM.
+const SNIFF_LEN: usize = 8192;
+
+/// The encoding a file was decoded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Encoding {
+    #[serde(rename = "utf-8")]
+    Utf8,
+    /// UTF-8 with a byte order mark
+    #[serde(rename = "utf-8-bom")]
+    Utf8Bom,
+    #[serde(rename = "utf-16le")]
+    Utf16Le,
+    #[serde(rename = "utf-16be")]
+    Utf16Be,
+    /// Windows-1252, used for content that is mostly not valid UTF-8
+    #[serde(rename = "windows-1252")]
+    Windows1252,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf8Bom => "UTF-8 with BOM",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Windows1252 => "Windows-1252",
+        })
+    }
+}
+
+/// The result of decoding a file. UTF-8 content is borrowed, not copied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decoded<'a> {
+    /// Text with the byte order mark removed. `lossy` is set when invalid
+    /// sequences, e.g. unpaired UTF-16 surrogates, were replaced.
+    Text {
+        content: Cow<'a, str>,
+        encoding: Encoding,
+        lossy: bool,
+    },
+    /// Contains NUL bytes and is not UTF-16
+    Binary,
+}
+
+/// Decode file content: a BOM selects UTF-8 or UTF-16, NUL bytes mark
+/// UTF-16 without a BOM or a binary file. Content that is not valid UTF-8
+/// is read as lossy UTF-8 if it has at least as many bytes in valid
+/// multibyte sequences as invalid bytes, and as Windows-1252 otherwise, so
+/// legacy files are still scanned.
+pub fn decode(bytes: &[u8]) -> Decoded<'_> {
+    if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        let content = String::from_utf8_lossy(rest);
+        return Decoded::Text {
+            lossy: matches!(content, Cow::Owned(_)),
+            content,
+            encoding: Encoding::Utf8Bom,
+        };
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+        return decode_utf16(rest, Encoding::Utf16Le);
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+        return decode_utf16(rest, Encoding::Utf16Be);
+    }
+
+    let head = &bytes[..bytes.len().min(SNIFF_LEN)];
+    if head.contains(&0) {
+        return match sniff_utf16(head) {
+            Some(encoding) => decode_utf16(bytes, encoding),
+            None => Decoded::Binary,
+        };
+    }
+
+    if let Ok(content) = std::str::from_utf8(bytes) {
+        return Decoded::Text {
+            content: Cow::Borrowed(content),
+            encoding: Encoding::Utf8,
+            lossy: false,
+        };
+    }
+
+    let (mut multibyte, mut invalid) = (0, 0);
+    for chunk in bytes.utf8_chunks() {
+        multibyte += chunk.valid().bytes().filter(|b| !b.is_ascii()).count();
+        invalid += chunk.invalid().len();
+    }
+    if multibyte >= invalid {
+        return Decoded::Text {
+            content: String::from_utf8_lossy(bytes),
+            encoding: Encoding::Utf8,
+            lossy: true,
+        };
+    }
+    // Every byte maps to a character, so this never fails
+    Decoded::Text {
+        content: Cow::Owned(bytes.iter().map(|&b| windows_1252(b)).collect()),
+        encoding: Encoding::Windows1252,
+        lossy: false,
+    }
+}
+
+/// Turn byte offsets into `content`, decoded from `bytes` as `encoding`,
+/// into offsets into `bytes` itself, counting any BOM.
+pub fn source_offsets<'a>(
+    bytes: &[u8],
+    content: &str,
+    encoding: Encoding,
+    offsets: impl IntoIterator<Item = &'a mut usize>,
+) {
+    // Replacing invalid UTF-8 only ever grows the content, so the same
+    // length means nothing was replaced
+    if encoding == Encoding::Utf8 && bytes.len() == content.len() {
+        return;
+    }
+    let bom = match encoding {
+        Encoding::Utf8Bom => 3,
+        Encoding::Utf16Le if bytes.starts_with(b"\xFF\xFE") => 2,
+        Encoding::Utf16Be if bytes.starts_with(b"\xFE\xFF") => 2,
+        _ => 0,
+    };
+    // Runs of (decoded, source) lengths, linear within each run
+    let runs: Box<dyn Iterator<Item = (usize, usize)> + '_> = match encoding {
+        Encoding::Utf8 | Encoding::Utf8Bom => {
+            Box::new(bytes[bom..].utf8_chunks().flat_map(|chunk| {
+                let valid = chunk.valid().len();
+                let invalid = chunk.invalid().len();
+                let replaced =
+                    (invalid > 0).then_some((char::REPLACEMENT_CHARACTER.len_utf8(), invalid));
+                std::iter::once((valid, valid)).chain(replaced)
+            }))
+        }
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            Box::new(content.chars().map(|c| (c.len_utf8(), c.len_utf16() * 2)))
+        }
+        Encoding::Windows1252 => Box::new(content.chars().map(|c| (c.len_utf8(), 1))),
+    };
+
+    let mut offsets: Vec<&mut usize> = offsets.into_iter().collect();
+    offsets.sort_by_key(|offset| **offset);
+    let mut runs = runs.peekable();
+    let (mut decoded, mut source) = (0, bom);
+    for offset in offsets {
+        while let Some((len, source_len)) = runs.next_if(|(len, _)| decoded + len <= *offset) {
+            decoded += len;
+            source += source_len;
+        }
+        *offset = source + (*offset - decoded);
+    }
+}
+
+/// Windows-1252 differs from ISO-8859-1 in 0x80-0x9F, where it has
+/// punctuation instead of control characters. The five unassigned bytes
+/// map to the control characters, as in the WHATWG encoding standard.
+fn windows_1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '\u{20AC}', '\u{81}', '\u{201A}', '\u{192}', '\u{201E}', '\u{2026}', '\u{2020}',
+        '\u{2021}', '\u{2C6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8D}', '\u{17D}',
+        '\u{8F}', '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}',
+        '\u{2014}', '\u{2DC}', '\u{2122}', '\u{161}', '\u{203A}', '\u{153}', '\u{9D}', '\u{17E}',
+        '\u{178}',
+    ];
+    match byte {
+        0x80..=0x9F => HIGH[usize::from(byte - 0x80)],
+        _ => char::from(byte),
+    }
+}
+
+/// Mostly-ASCII UTF-16 has a NUL in every other byte, on the high side of
+/// each code unit, and hardly any elsewhere.
+fn sniff_utf16(head: &[u8]) -> Option<Encoding> {
+    let units = head.len() / 2;
+    if units == 0 {
+        return None;
+    }
+    let nuls = |offset: usize| {
+        head.iter()
+            .skip(offset)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count()
+    };
+    let (even, odd) = (nuls(0), nuls(1));
+    if odd * 10 >= units * 9 && even * 10 <= units {
+        Some(Encoding::Utf16Le)
+    } else if even * 10 >= units * 9 && odd * 10 <= units {
+        Some(Encoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+fn decode_utf16(bytes: &[u8], encoding: Encoding) -> Decoded<'static> {
+    let units = bytes.chunks_exact(2).map(|pair| match encoding {
+        Encoding::Utf16Be => u16::from_be_bytes([pair[0], pair[1]]),
+        _ => u16::from_le_bytes([pair[0], pair[1]]),
+    });
+    let mut lossy = bytes.len() % 2 != 0;
+    let content: String = char::decode_utf16(units)
+        .map(|unit| {
+            unit.unwrap_or_else(|_| {
+                lossy = true;
+                char::REPLACEMENT_CHARACTER
+            })
+        })
+        .collect();
+    Decoded::Text {
+        content: Cow::Owned(content),
+        encoding,
+        lossy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(bytes: &[u8]) -> (String, Encoding) {
+        match decode(bytes) {
+            Decoded::Text {
+                content, encoding, ..
+            } => (content.into_owned(), encoding),
+            Decoded::Binary => panic!("decoded as binary"),
+        }
+    }
+
+    fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| {
+                if big_endian {
+                    unit.to_be_bytes()
+                } else {
+                    unit.to_le_bytes()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_decode() {
+        let source = "// TODO: café\r\n";
+        assert_eq!(text(source.as_bytes()), (source.into(), Encoding::Utf8));
+        assert_eq!(
+            text(&[b"\xEF\xBB\xBF", source.as_bytes()].concat()),
+            (source.into(), Encoding::Utf8Bom)
+        );
+        assert_eq!(
+            text(b"/* TODO: caf\xe9 \x93quoted\x94 */"),
+            (
+                "/* TODO: café \u{201C}quoted\u{201D} */".into(),
+                Encoding::Windows1252
+            )
+        );
+        // Mostly UTF-8 with a stray byte keeps its multibyte characters
+        assert_eq!(
+            decode(&[" // TODO: café, naïve \u{2014} ".as_bytes(), b"\xff"].concat()),
+            Decoded::Text {
+                content: Cow::Borrowed(" // TODO: café, naïve \u{2014} \u{FFFD}"),
+                encoding: Encoding::Utf8,
+                lossy: true,
+            }
+        );
+
+        let le = [b"\xFF\xFE".as_slice(), &utf16(source, false)].concat();
+        assert_eq!(text(&le), (source.into(), Encoding::Utf16Le));
+        let be = [b"\xFE\xFF".as_slice(), &utf16(source, true)].concat();
+        assert_eq!(text(&be), (source.into(), Encoding::Utf16Be));
+        // Without a BOM, recognized by where the NULs are
+        assert_eq!(
+            text(&utf16(source, false)),
+            (source.into(), Encoding::Utf16Le)
+        );
+        assert_eq!(
+            text(&utf16(source, true)),
+            (source.into(), Encoding::Utf16Be)
+        );
+
+        assert_eq!(
+            decode(b"\x7fELF\x02\x01\x01\x00\x00\x00TODO"),
+            Decoded::Binary
+        );
+        assert!(matches!(
+            decode(&[0xFF, 0xFE, 0x00, 0xD8, b'a', 0x00]),
+            Decoded::Text { lossy: true, .. }
+        ));
+    }
+
+    #[test]
+    fn test_source_offsets() {
+        let source = "// café: TODO\n";
+        let todo = source.find("TODO").unwrap();
+        let offset_in = |bytes: &[u8]| {
+            let Decoded::Text {
+                content, encoding, ..
+            } = decode(bytes)
+            else {
+                panic!("decoded as binary");
+            };
+            let mut offset = content.find("TODO").unwrap();
+            source_offsets(bytes, &content, encoding, [&mut offset]);
+            offset
+        };
+
+        assert_eq!(offset_in(source.as_bytes()), todo);
+        assert_eq!(
+            offset_in(&[b"\xEF\xBB\xBF", source.as_bytes()].concat()),
+            todo + 3
+        );
+        // "é" is two bytes in UTF-8 and one in Windows-1252
+        assert_eq!(offset_in(b"// caf\xe9: TODO\n"), todo - 1);
+        // The invalid bytes were each replaced by a three byte U+FFFD
+        assert_eq!(offset_in(b"// caf\xc3\xa9 \xff\xfe: TODO\n"), todo + 3);
+        assert_eq!(
+            offset_in(&[b"\xFF\xFE".as_slice(), &utf16(source, false)].concat()),
+            2 + 2 * 9
+        );
+        assert_eq!(offset_in(&utf16(source, true)), 2 * 9);
+    }
+}
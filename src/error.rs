@@ -38,8 +38,8 @@ pub fn handle_path_error(path: &str) -> CodeDebtError {
         CodeDebtError::new(format!("Path '{}' does not exist", path))
             .with_suggestion("Check the path and try again. Use '.' for current directory.")
     } else if !std::path::Path::new(path).is_dir() {
-        CodeDebtError::new(format!("Path '{}' is not a directory", path))
-            .with_suggestion("Please provide a directory path, not a file.")
+        CodeDebtError::new(format!("Path '{}' is not a file or directory", path))
+            .with_suggestion("Please provide a file or directory path.")
     } else {
         CodeDebtError::new(format!("Cannot access path '{}'", path))
             .with_suggestion("Check permissions and try again.")
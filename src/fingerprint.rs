@@ -0,0 +1,61 @@
+use sha2::{Digest, Sha256};
+
+/// Identity of a debt item that survives line shifts, reindentation and
+/// file renames: a hash of the pattern, the whitespace-normalized line and
+/// the nearest non-blank lines around it. The file path is left out, so
+/// callers that need a global identity pair it with the (rename-followed)
+/// path.
+pub fn fingerprint(pattern: &str, line: &str, before: Option<&str>, after: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(pattern.as_bytes());
+    for part in [Some(line), before, after] {
+        hasher.update([0]);
+        hasher.update(normalize(part.unwrap_or("")).as_bytes());
+    }
+    hex(&hasher.finalize()[..16])
+}
+
+/// Fingerprint of the item on `line_idx` (0-based) of `lines`.
+pub fn fingerprint_in(pattern: &str, lines: &[&str], line_idx: usize) -> String {
+    let before = lines
+        .iter()
+        .take(line_idx)
+        .rev()
+        .find(|line| !line.trim().is_empty());
+    let after = lines
+        .iter()
+        .skip(line_idx + 1)
+        .find(|line| !line.trim().is_empty());
+    fingerprint(
+        pattern,
+        lines.get(line_idx).copied().unwrap_or(""),
+        before.copied(),
+        after.copied(),
+    )
+}
+
+/// Collapse runs of whitespace and trim, so reindenting is not a change.
+pub fn normalize(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_uses_context_not_position() {
+        let original = ["fn a() {", "    // TODO: fix", "}"];
+        let shifted = ["", "// header", "fn a() {", "", "  // TODO:   fix", "}"];
+        let other = ["fn b() {", "    // TODO: fix", "}"];
+
+        let fp = fingerprint_in("TODO", &original, 1);
+        assert_eq!(fp, fingerprint_in("TODO", &shifted, 4));
+        assert_ne!(fp, fingerprint_in("TODO", &other, 1));
+        assert_ne!(fp, fingerprint_in("FIXME", &original, 1));
+    }
+}
@@ -1,91 +1,693 @@
-use crate::models::CodeDebtItem;
+use crate::blame::{
+    old_line, BlameCache, BlameConfig, BlameHunk, BlameSettings, FileBlame, OldLine,
+};
+use crate::models::{BlameStatus, CodeDebtItem, FileChurn};
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use git2::{BlameOptions, Repository};
+use git2::{Delta, DiffFindOptions, DiffOptions, ObjectType, Oid, Patch, Repository, Sort};
 use log::{debug, warn};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 pub struct GitAnalyzer;
 
+/// Blame of one working-tree line.
+#[derive(Debug, Clone)]
+enum LineBlame {
+    Committed(BlameHunk),
+    Uncommitted,
+    Untracked,
+    Error,
+}
+
+/// Where the repository owning a directory lives.
+#[derive(Debug, Clone)]
+struct RepoLocation {
+    git_dir: PathBuf,
+    workdir: PathBuf,
+}
+
+/// Finds the repository owning each directory, including nested checkouts
+/// and submodules, discovering it once per directory.
+#[derive(Default)]
+struct RepoLocator {
+    dirs: HashMap<PathBuf, Option<RepoLocation>>,
+}
+
+impl RepoLocator {
+    fn locate(&mut self, dir: &Path) -> Option<RepoLocation> {
+        self.dirs
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let repo = Repository::discover(dir).ok()?;
+                let workdir = repo.workdir()?.canonicalize().ok()?;
+                Some(RepoLocation {
+                    git_dir: repo.path().to_path_buf(),
+                    workdir,
+                })
+            })
+            .clone()
+    }
+}
+
+/// Lines added and removed between a base commit and the working tree.
+#[derive(Debug)]
+pub struct LineChanges {
+    pub base: Oid,
+    /// Added line numbers in the working tree, by path relative to the repository root
+    pub added: HashMap<PathBuf, HashSet<usize>>,
+    /// Removed line numbers in the base commit, by path relative to the repository root
+    pub removed: HashMap<PathBuf, HashSet<usize>>,
+}
+
+/// Commits this much older than a churn window are still walked, in case
+/// a parent's clock was ahead of its child's.
+const CHURN_CLOCK_SKEW_SECS: i64 = 24 * 60 * 60;
+
 impl GitAnalyzer {
-    pub fn add_git_information(git_repo: Option<&Repository>, items: &mut [CodeDebtItem]) {
-        if let Some(repo) = git_repo {
-            debug!("Adding git blame information to {} items", items.len());
-            let mut success_count = 0;
-            let mut error_count = 0;
-
-            for item in items.iter_mut() {
-                let workdir = match repo.workdir() {
-                    Some(dir) => dir,
-                    None => {
-                        debug!("Repository has no working directory");
-                        error_count += 1;
-                        continue;
+    /// Diff the working tree (including untracked files) against the
+    /// merge-base of HEAD and `rev`.
+    pub fn changes_since(repo: &Repository, rev: &str) -> Result<LineChanges> {
+        let target = repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_commit())
+            .with_context(|| format!("Unknown git revision '{}'", rev))?;
+        let head = repo.head()?.peel_to_commit()?;
+        let base = repo
+            .merge_base(head.id(), target.id())
+            .with_context(|| format!("No merge-base between HEAD and '{}'", rev))?;
+        let base_tree = repo.find_commit(base)?.tree()?;
+
+        let mut options = DiffOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true)
+            .context_lines(0);
+        let diff = repo.diff_tree_to_workdir_with_index(Some(&base_tree), Some(&mut options))?;
+
+        let mut changes = LineChanges {
+            base,
+            added: HashMap::new(),
+            removed: HashMap::new(),
+        };
+        diff.foreach(
+            &mut |_, _| true,
+            None,
+            None,
+            Some(&mut |delta, _, line| {
+                match line.origin() {
+                    '+' => {
+                        if let (Some(path), Some(lineno)) =
+                            (delta.new_file().path(), line.new_lineno())
+                        {
+                            changes
+                                .added
+                                .entry(path.to_path_buf())
+                                .or_default()
+                                .insert(lineno as usize);
+                        }
+                    }
+                    '-' => {
+                        if let (Some(path), Some(lineno)) =
+                            (delta.old_file().path(), line.old_lineno())
+                        {
+                            changes
+                                .removed
+                                .entry(path.to_path_buf())
+                                .or_default()
+                                .insert(lineno as usize);
+                        }
                     }
+                    _ => {}
+                }
+                true
+            }),
+        )?;
+
+        debug!(
+            "{} files with added lines and {} with removed lines since {}",
+            changes.added.len(),
+            changes.removed.len(),
+            base
+        );
+        Ok(changes)
+    }
+
+    /// Files renamed between `rev` and the working tree, including untracked
+    /// files, as old path to new path relative to the repository root.
+    pub fn renames_since(repo: &Repository, rev: &str) -> Result<HashMap<PathBuf, PathBuf>> {
+        let tree = repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_tree())
+            .with_context(|| format!("Unknown git revision '{}'", rev))?;
+        let mut options = DiffOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let mut diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+        let mut find = DiffFindOptions::new();
+        find.renames(true).for_untracked(true);
+        diff.find_similar(Some(&mut find))?;
+
+        Ok(diff
+            .deltas()
+            .filter(|delta| delta.status() == Delta::Renamed)
+            .filter_map(|delta| {
+                Some((
+                    delta.old_file().path()?.to_path_buf(),
+                    delta.new_file().path()?.to_path_buf(),
+                ))
+            })
+            .collect())
+    }
+
+    /// Churn of each file in the non-merge commits reachable from HEAD with
+    /// a commit date in `since..=until`, by path relative to the repository
+    /// root. Changes made before a rename count towards the file's current
+    /// name.
+    pub fn churn_between(
+        repo: &Repository,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<HashMap<PathBuf, FileChurn>> {
+        let mut walk = repo.revwalk()?;
+        walk.push_head()?;
+        walk.set_sorting(Sort::TIME)?;
+
+        let mut churn: HashMap<PathBuf, FileChurn> = HashMap::new();
+        // Older names of renamed files, mapped to their newest name
+        let mut renamed: HashMap<PathBuf, PathBuf> = HashMap::new();
+        let mut options = DiffOptions::new();
+        options.context_lines(0);
+        for oid in walk {
+            let commit = repo.find_commit(oid?)?;
+            let time = commit.time().seconds();
+            // Newest first, so the rest of history is older than the window
+            if time < since.timestamp() - CHURN_CLOCK_SKEW_SECS {
+                break;
+            }
+            if commit.parent_count() != 1 || time < since.timestamp() || time > until.timestamp() {
+                continue;
+            }
+
+            let parent_tree = commit.parent(0)?.tree()?;
+            let mut diff = repo.diff_tree_to_tree(
+                Some(&parent_tree),
+                Some(&commit.tree()?),
+                Some(&mut options),
+            )?;
+            diff.find_similar(None)?;
+            for idx in 0..diff.deltas().len() {
+                let Some(delta) = diff.get_delta(idx) else {
+                    continue;
+                };
+                let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+                    continue;
+                };
+                let current = renamed.get(path).cloned().unwrap_or(path.to_path_buf());
+                if delta.status() == Delta::Renamed {
+                    if let Some(old) = delta.old_file().path() {
+                        renamed.insert(old.to_path_buf(), current.clone());
+                    }
+                }
+
+                let (added, removed) = match Patch::from_diff(&diff, idx)? {
+                    Some(patch) => {
+                        let (_, added, removed) = patch.line_stats()?;
+                        (added, removed)
+                    }
+                    None => (0, 0),
                 };
+                let entry = churn.entry(current).or_default();
+                entry.commits += 1;
+                entry.lines_added += added;
+                entry.lines_removed += removed;
+            }
+        }
+        Ok(churn)
+    }
+
+    /// Churn of each of `paths` between `since` and `until`, see
+    /// [`GitAnalyzer::churn_between`]. Each repository is walked once, and
+    /// files outside a repository or without changes are left out.
+    pub fn file_churn(
+        paths: &[PathBuf],
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> HashMap<PathBuf, FileChurn> {
+        let mut locator = RepoLocator::default();
+        let mut by_repo: HashMap<PathBuf, Vec<(&PathBuf, PathBuf)>> = HashMap::new();
+        for path in paths {
+            let Some(canonical) = path.canonicalize().ok() else {
+                continue;
+            };
+            let Some(location) = canonical.parent().and_then(|dir| locator.locate(dir)) else {
+                continue;
+            };
+            if let Ok(relative) = canonical.strip_prefix(&location.workdir) {
+                by_repo
+                    .entry(location.git_dir)
+                    .or_default()
+                    .push((path, relative.to_path_buf()));
+            }
+        }
+
+        let mut result = HashMap::new();
+        for (git_dir, files) in by_repo {
+            let churn = match Repository::open(&git_dir)
+                .map_err(anyhow::Error::from)
+                .and_then(|repo| Self::churn_between(&repo, since, until))
+            {
+                Ok(churn) => churn,
+                Err(e) => {
+                    warn!("Failed to read history of {}: {}", git_dir.display(), e);
+                    continue;
+                }
+            };
+            for (path, relative) in files {
+                if let Some(file_churn) = churn.get(&relative) {
+                    result.insert(path.clone(), file_churn.clone());
+                }
+            }
+        }
+        result
+    }
+
+    /// Fill in the owning repository, blame status, author, commit and age
+    /// of items. Each item's repository is discovered from its own path, so
+    /// nested repositories and submodules are blamed separately. Each file
+    /// is blamed once, in parallel, and the result is cached by blob id.
+    ///
+    /// Lines changed in the working tree and untracked files are attributed
+    /// to `user.name` from git config with an age of zero days. Other ages
+    /// are measured from the date picked by `config.age_from` up to `now`.
+    pub fn add_git_information(
+        items: &mut [CodeDebtItem],
+        config: &BlameConfig,
+        now: DateTime<Utc>,
+    ) {
+        debug!("Adding git blame information to {} items", items.len());
+
+        // Group items by repository and path relative to its root
+        let mut locator = RepoLocator::default();
+        let mut by_file: HashMap<(PathBuf, PathBuf), Vec<usize>> = HashMap::new();
+        for (idx, item) in items.iter_mut().enumerate() {
+            let Some(path) = item.file_path.canonicalize().ok() else {
+                debug!("Failed to resolve {}", item.file_path.display());
+                item.blame_status = Some(BlameStatus::Error);
+                continue;
+            };
+            let Some(location) = path.parent().and_then(|dir| locator.locate(dir)) else {
+                item.blame_status = Some(BlameStatus::OutsideRepo);
+                continue;
+            };
+            let Ok(relative) = path.strip_prefix(&location.workdir) else {
+                item.blame_status = Some(BlameStatus::Error);
+                continue;
+            };
+            by_file
+                .entry((location.git_dir.clone(), relative.to_path_buf()))
+                .or_default()
+                .push(idx);
+            item.repo_path = Some(location.workdir);
+        }
+
+        let files: Vec<((PathBuf, PathBuf), Vec<usize>)> = by_file.into_iter().collect();
+        let results: Vec<(Vec<LineBlame>, Option<String>)> = files
+            .par_iter()
+            .map_init(
+                // Repository handles can't be shared between threads, so
+                // each worker opens its own
+                HashMap::<PathBuf, Option<(Repository, BlameSettings, Option<String>)>>::new,
+                |repos, ((git_dir, path), indices)| {
+                    let repo = repos.entry(git_dir.clone()).or_insert_with(|| {
+                        let repo = Repository::open(git_dir).ok()?;
+                        let settings = BlameSettings::resolve(&repo, config);
+                        let user = repo
+                            .config()
+                            .and_then(|config| config.get_string("user.name"))
+                            .ok();
+                        Some((repo, settings, user))
+                    });
+                    let lines: Vec<usize> =
+                        indices.iter().map(|&idx| items[idx].line_number).collect();
+                    match repo {
+                        Some((repo, settings, user)) => (
+                            Self::blame_lines(repo, path, &lines, settings),
+                            user.clone(),
+                        ),
+                        None => (vec![LineBlame::Error; lines.len()], None),
+                    }
+                },
+            )
+            .collect();
 
-                match item.file_path.strip_prefix(workdir) {
-                    Ok(relative_path) => {
-                        match repo.blame_file(relative_path, Some(&mut BlameOptions::new())) {
-                            Ok(blame) => {
-                                if let Some(hunk) = blame.get_line(item.line_number) {
-                                    let sig = hunk.final_signature();
-                                    let oid = hunk.final_commit_id();
-
-                                    item.author = sig.name().map(|s| s.to_string());
-                                    item.commit_hash = Some(oid.to_string());
-
-                                    if let Ok(commit) = repo.find_commit(oid) {
-                                        let timestamp = commit.time().seconds();
-                                        if let Some(datetime) =
-                                            DateTime::from_timestamp(timestamp, 0)
-                                        {
-                                            item.created_at = Some(datetime);
-                                            let now = Utc::now();
-                                            let duration = now.signed_duration_since(datetime);
-                                            item.age_days = Some(duration.num_days());
-                                            success_count += 1;
-                                        } else {
-                                            debug!(
-                                                "Failed to parse timestamp {} for commit {}",
-                                                timestamp, oid
-                                            );
-                                            error_count += 1;
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                debug!(
-                                    "Failed to get git blame for {}:{} - {}",
-                                    item.file_path.display(),
-                                    item.line_number,
-                                    e
-                                );
-                                error_count += 1;
-                            }
+        let mut error_count = 0;
+        for ((_, indices), (lines, user)) in files.iter().zip(results) {
+            for (&idx, line) in indices.iter().zip(lines) {
+                let item = &mut items[idx];
+                match line {
+                    LineBlame::Committed(hunk) => {
+                        item.blame_status = Some(BlameStatus::Committed);
+                        let time = hunk.time(config.age_from);
+                        item.author = hunk.author;
+                        item.commit_hash = Some(hunk.commit);
+                        if let Some(datetime) = DateTime::from_timestamp(time, 0) {
+                            item.created_at = Some(datetime);
+                            // Lines from after the reference time count as new
+                            let age = now.signed_duration_since(datetime).num_days();
+                            item.age_days = Some(age.max(0));
                         }
                     }
-                    Err(e) => {
-                        debug!(
-                            "Failed to get relative path for {} - {}",
-                            item.file_path.display(),
-                            e
-                        );
+                    LineBlame::Uncommitted | LineBlame::Untracked => {
+                        item.blame_status = Some(if matches!(line, LineBlame::Uncommitted) {
+                            BlameStatus::Uncommitted
+                        } else {
+                            BlameStatus::Untracked
+                        });
+                        item.author = user.clone();
+                        item.age_days = Some(0);
+                    }
+                    LineBlame::Error => {
+                        item.blame_status = Some(BlameStatus::Error);
                         error_count += 1;
                     }
                 }
             }
+        }
+
+        if error_count > 0 {
+            warn!("Git blame failed for {} items", error_count);
+        } else {
+            debug!("Git blame completed for all {} items", items.len());
+        }
+    }
 
-            if error_count > 0 {
-                warn!(
-                    "Git blame completed with {} successes and {} errors",
-                    success_count, error_count
-                );
+    /// Blame the given working-tree lines of a file.
+    fn blame_lines(
+        repo: &Repository,
+        path: &Path,
+        lines: &[usize],
+        settings: &BlameSettings,
+    ) -> Vec<LineBlame> {
+        let all = |status: LineBlame| vec![status; lines.len()];
+        let Some(content) = repo
+            .workdir()
+            .and_then(|workdir| std::fs::read(workdir.join(path)).ok())
+        else {
+            return all(LineBlame::Error);
+        };
+
+        let head_blob = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .and_then(|head| Ok((head.id(), head.tree()?.get_path(path)?.id())));
+        let Ok((head, head_blob)) = head_blob else {
+            // Added to the index but never committed counts as uncommitted
+            let staged = repo
+                .index()
+                .is_ok_and(|index| index.get_path(path, 0).is_some());
+            return all(if staged {
+                LineBlame::Uncommitted
             } else {
-                debug!(
-                    "Git blame completed successfully for all {} items",
-                    success_count
-                );
+                LineBlame::Untracked
+            });
+        };
+
+        // Only committed files can be blamed; their blob id keys the cache
+        let Some(blame) = Self::blame_file(repo, head, path, head_blob, settings) else {
+            return all(LineBlame::Error);
+        };
+        let committed = |line: usize| {
+            blame
+                .line(line)
+                .cloned()
+                .map_or(LineBlame::Error, LineBlame::Committed)
+        };
+
+        if Oid::hash_object(ObjectType::Blob, &content).ok() == Some(head_blob) {
+            return lines.iter().map(|&line| committed(line)).collect();
+        }
+
+        // Map working-tree lines to HEAD, lines that differ are uncommitted
+        let mut options = DiffOptions::new();
+        options
+            .context_lines(0)
+            .ignore_whitespace(settings.ignore_whitespace);
+        let patch = repo.find_blob(head_blob).and_then(|blob| {
+            Patch::from_blob_and_buffer(&blob, Some(path), &content, Some(path), Some(&mut options))
+        });
+        let Ok(patch) = patch else {
+            return all(LineBlame::Error);
+        };
+        lines
+            .iter()
+            .map(|&line| match old_line(&patch, line) {
+                Some(OldLine::Unchanged(old)) => committed(old),
+                Some(OldLine::Changed(_)) => LineBlame::Uncommitted,
+                None => LineBlame::Error,
+            })
+            .collect()
+    }
+
+    /// Blame `path` as of `commit`, where it has content `blob`, using the
+    /// cache when possible.
+    pub(crate) fn blame_file(
+        repo: &Repository,
+        commit: Oid,
+        path: &Path,
+        blob: Oid,
+        settings: &BlameSettings,
+    ) -> Option<FileBlame> {
+        let cache = BlameCache::for_repo(repo);
+        let key = BlameCache::key(path, blob, settings);
+        if let Some(blame) = cache.get(&key) {
+            return Some(blame);
+        }
+
+        match FileBlame::compute_at(repo, commit, path, settings) {
+            Ok(blame) => {
+                cache.put(&key, &blame);
+                Some(blame)
+            }
+            Err(e) => {
+                debug!("Failed to get git blame for {} - {}", path.display(), e);
+                None
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blame::AgeFrom;
+    use crate::models::Severity;
+    use crate::test_support::{commit, commit_as, now};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_nested_repositories_are_blamed_separately() {
+        let temp_dir = TempDir::new().unwrap();
+        let outer = Repository::init(temp_dir.path()).unwrap();
+        let inner_dir = temp_dir.path().join("vendor-checkout");
+        fs::create_dir(&inner_dir).unwrap();
+        let inner = Repository::init(&inner_dir).unwrap();
+
+        commit(
+            &outer,
+            &[("a.rs", Some("// TODO: outer\n"))],
+            "Outer Dev",
+            now(),
+        );
+        commit(
+            &inner,
+            &[("b.rs", Some("// TODO: inner\n"))],
+            "Inner Dev",
+            now(),
+        );
+
+        let mut items = vec![
+            CodeDebtItem::new(
+                temp_dir.path().join("a.rs"),
+                1,
+                4,
+                "// TODO: outer",
+                "TODO",
+                Severity::Medium,
+            ),
+            CodeDebtItem::new(
+                inner_dir.join("b.rs"),
+                1,
+                4,
+                "// TODO: inner",
+                "TODO",
+                Severity::Medium,
+            ),
+        ];
+        GitAnalyzer::add_git_information(&mut items, &BlameConfig::default(), Utc::now());
+
+        assert_eq!(items[0].author.as_deref(), Some("Outer Dev"));
+        assert_eq!(items[1].author.as_deref(), Some("Inner Dev"));
+        assert_eq!(
+            items[0].repo_path,
+            Some(temp_dir.path().canonicalize().unwrap())
+        );
+        assert_eq!(items[1].repo_path, Some(inner_dir.canonicalize().unwrap()));
+    }
+
+    #[test]
+    fn test_blame_status_for_working_tree_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        repo.config()
+            .unwrap()
+            .set_str("user.name", "Local Dev")
+            .unwrap();
+        commit(
+            &repo,
+            &[("a.rs", Some("// TODO: old\nfn a() {}\n"))],
+            "Committer",
+            now(),
+        );
+
+        let workdir = temp_dir.path();
+        fs::write(workdir.join("a.rs"), "// TODO: old\n// FIXME: new\n").unwrap();
+        fs::write(workdir.join("staged.rs"), "// TODO: staged\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("staged.rs")).unwrap();
+        index.write().unwrap();
+        fs::write(workdir.join("untracked.rs"), "// TODO: untracked\n").unwrap();
+
+        let outside = TempDir::new().unwrap();
+        fs::write(outside.path().join("loose.rs"), "// TODO: loose\n").unwrap();
+
+        let item = |path: PathBuf, line| {
+            CodeDebtItem::new(path, line, 4, "// TODO", "TODO", Severity::Medium)
+        };
+        let mut items = vec![
+            item(workdir.join("a.rs"), 1),
+            item(workdir.join("a.rs"), 2),
+            item(workdir.join("staged.rs"), 1),
+            item(workdir.join("untracked.rs"), 1),
+            item(outside.path().join("loose.rs"), 1),
+        ];
+        GitAnalyzer::add_git_information(&mut items, &BlameConfig::default(), Utc::now());
+
+        let statuses: Vec<_> = items.iter().map(|i| i.blame_status.unwrap()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                BlameStatus::Committed,
+                BlameStatus::Uncommitted,
+                BlameStatus::Uncommitted,
+                BlameStatus::Untracked,
+                BlameStatus::OutsideRepo,
+            ]
+        );
+        assert_eq!(items[0].author.as_deref(), Some("Committer"));
+        for item in &items[1..4] {
+            assert_eq!(item.author.as_deref(), Some("Local Dev"));
+            assert_eq!(item.age_days, Some(0));
+            assert!(item.commit_hash.is_none());
+        }
+        assert!(items[4].author.is_none());
+    }
+
+    #[test]
+    fn test_ages_from_reference_time() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let day = 24 * 60 * 60;
+        let written = 1_700_000_000;
+        let author = git2::Signature::new("Dev", "dev@example.com", &git2::Time::new(written, 0));
+        let committer = git2::Signature::new(
+            "Dev",
+            "dev@example.com",
+            &git2::Time::new(written + 10 * day, 0),
+        );
+        commit_as(
+            &repo,
+            &[("a.rs", Some("// TODO: rebased\n"))],
+            &author.unwrap(),
+            &committer.unwrap(),
+        );
+
+        let now = DateTime::from_timestamp(written + 30 * day, 0).unwrap();
+        let age = |config: &BlameConfig, now| {
+            let mut items = vec![CodeDebtItem::new(
+                temp_dir.path().join("a.rs"),
+                1,
+                4,
+                "// TODO: rebased",
+                "TODO",
+                Severity::Medium,
+            )];
+            GitAnalyzer::add_git_information(&mut items, config, now);
+            items[0].age_days.unwrap()
+        };
+
+        let mut config = BlameConfig::default();
+        assert_eq!(age(&config, now), 20);
+        config.age_from = AgeFrom::Author;
+        assert_eq!(age(&config, now), 30);
+        assert_eq!(age(&config, DateTime::from_timestamp(0, 0).unwrap()), 0);
+    }
+
+    #[test]
+    fn test_hotspots_rank_debt_by_recent_churn() {
+        let temp_dir = TempDir::new().unwrap();
+        let workdir = temp_dir.path();
+        let repo = Repository::init(workdir).unwrap();
+        let day = 24 * 60 * 60;
+        let commit = |files: &[(&str, Option<&str>)], at: i64| {
+            commit(&repo, files, "Dev", at * day);
+        };
+
+        let old = "// TODO: old\nfn old() {}\n";
+        commit(
+            &[
+                ("a.rs", Some("// TODO: a\n")),
+                ("old.rs", Some(old)),
+                ("quiet.rs", Some("// HACK: untouched\n")),
+            ],
+            0,
+        );
+        commit(&[("a.rs", Some("// TODO: a\nfn a() {}\n"))], 50);
+        let old = "// TODO: old\nfn old() {}\nfn more() {}\n";
+        commit(&[("old.rs", Some(old))], 75);
+        commit(&[("old.rs", None), ("b.rs", Some(old))], 80);
+        commit(
+            &[
+                ("a.rs", Some("// TODO: a\nfn a() { 1 }\n")),
+                ("b.rs", Some("// TODO: old\nfn more() {}\n")),
+            ],
+            90,
+        );
+        commit(&[("a.rs", Some("// TODO: a\nfn a() { 2 }\n"))], 95);
+
+        let now = DateTime::from_timestamp(100 * day, 0).unwrap();
+        let churn =
+            GitAnalyzer::churn_between(&repo, now - chrono::Duration::days(30), now).unwrap();
+        // The change to old.rs before the rename counts towards b.rs
+        let b = &churn[Path::new("b.rs")];
+        assert_eq!((b.commits, b.lines_added, b.lines_removed), (3, 1, 1));
+        assert_eq!(churn[Path::new("a.rs")].commits, 2);
+        assert!(!churn.contains_key(Path::new("quiet.rs")));
+
+        let scanner = crate::scanner::CodeDebtScanner::new().with_reference_time(now);
+        let items = scanner.scan(workdir).unwrap();
+        let hotspots = scanner.get_hotspots(&items, 30);
+        let ranked: Vec<_> = hotspots
+            .iter()
+            .map(|hotspot| {
+                (
+                    hotspot.file_path.file_name().unwrap().to_str().unwrap(),
+                    hotspot.score,
+                )
+            })
+            .collect();
+        assert_eq!(ranked, vec![("b.rs", 6), ("a.rs", 4)]);
+    }
+}
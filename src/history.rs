@@ -0,0 +1,275 @@
+use crate::models::Severity;
+use crate::revision::{scan_blobs, TreeFile, TreeWalker};
+use crate::scanner::{CodeDebtScanner, FileScan};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use git2::{Commit, Oid, Repository};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Which commits to count debt at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sampling {
+    /// Every `n`th commit on the first-parent history of the revision
+    FirstParent { every: usize },
+    /// Every tag that points at a commit
+    Tags,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Sampling::FirstParent { every: 1 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryOptions {
+    /// Where the first-parent walk starts
+    pub rev: String,
+    pub sampling: Sampling,
+    /// Keep only the most recent points
+    pub limit: Option<usize>,
+    /// Only count files under this path, relative to the repository root
+    pub prefix: Option<PathBuf>,
+}
+
+impl Default for HistoryOptions {
+    fn default() -> Self {
+        Self {
+            rev: "HEAD".to_string(),
+            sampling: Sampling::default(),
+            limit: None,
+            prefix: None,
+        }
+    }
+}
+
+/// Debt counts at one commit.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPoint {
+    pub commit: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Commit date
+    pub date: DateTime<Utc>,
+    pub total: usize,
+    pub by_severity: BTreeMap<Severity, usize>,
+    pub by_pattern: BTreeMap<String, usize>,
+}
+
+/// Count debt at sampled commits, oldest first. Files are read from the
+/// object database, so nothing is checked out, and each blob is scanned
+/// only once however many commits contain it.
+pub fn debt_history(
+    scanner: &CodeDebtScanner,
+    repo: &Repository,
+    options: &HistoryOptions,
+) -> Result<Vec<HistoryPoint>> {
+    let mut commits = sample_commits(repo, options)?;
+    if let Some(limit) = options.limit {
+        let skip = commits.len().saturating_sub(limit);
+        commits.drain(..skip);
+    }
+
+    let walker = TreeWalker::new(scanner)?;
+    let severities: HashMap<&str, &Severity> = scanner
+        .patterns()
+        .iter()
+        .map(|pattern| (pattern.name.as_str(), &pattern.severity))
+        .collect();
+    let mut counts = BlobCounts::default();
+
+    let mut points = Vec::with_capacity(commits.len());
+    for (oid, tag) in commits {
+        let commit = repo.find_commit(oid)?;
+        let files = walker.files(repo, &commit.tree()?, options.prefix.as_deref())?;
+        counts.scan_missing(scanner, repo, &files)?;
+
+        let mut by_pattern: BTreeMap<String, usize> = BTreeMap::new();
+        for file in &files {
+            for (pattern, count) in counts.get(file) {
+                *by_pattern.entry(pattern.clone()).or_insert(0) += count;
+            }
+        }
+        let mut by_severity = BTreeMap::new();
+        for (pattern, count) in &by_pattern {
+            if let Some(&severity) = severities.get(pattern.as_str()) {
+                *by_severity.entry(severity.clone()).or_insert(0) += count;
+            }
+        }
+
+        points.push(HistoryPoint {
+            commit: oid.to_string(),
+            tag,
+            date: commit_date(&commit),
+            total: by_pattern.values().sum(),
+            by_severity,
+            by_pattern,
+        });
+    }
+    Ok(points)
+}
+
+/// The sampled commits, oldest first, with the tag they were found by.
+fn sample_commits(
+    repo: &Repository,
+    options: &HistoryOptions,
+) -> Result<Vec<(Oid, Option<String>)>> {
+    match options.sampling {
+        Sampling::FirstParent { every } => {
+            let start = repo
+                .revparse_single(&options.rev)
+                .and_then(|object| object.peel_to_commit())
+                .with_context(|| format!("Unknown git revision '{}'", options.rev))?;
+            let mut walk = repo.revwalk()?;
+            walk.push(start.id())?;
+            walk.simplify_first_parent()?;
+            let mut commits = walk
+                .step_by(every.max(1))
+                .map(|oid| Ok((oid?, None)))
+                .collect::<Result<Vec<_>>>()?;
+            commits.reverse();
+            Ok(commits)
+        }
+        Sampling::Tags => {
+            let mut tagged = Vec::new();
+            for name in repo.tag_names(None)?.iter().flatten() {
+                let Ok(commit) = repo
+                    .revparse_single(&format!("refs/tags/{}", name))
+                    .and_then(|object| object.peel_to_commit())
+                else {
+                    continue;
+                };
+                tagged.push((commit.time().seconds(), commit.id(), name.to_string()));
+            }
+            tagged.sort();
+            Ok(tagged
+                .into_iter()
+                .map(|(_, oid, name)| (oid, Some(name)))
+                .collect())
+        }
+    }
+}
+
+fn commit_date(commit: &Commit) -> DateTime<Utc> {
+    DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default()
+}
+
+/// Per-pattern counts of each scanned blob. The extension is part of the
+/// key because it selects the comment syntax.
+#[derive(Default)]
+struct BlobCounts {
+    counts: HashMap<(Oid, String), BTreeMap<String, usize>>,
+}
+
+impl BlobCounts {
+    fn key(file: &TreeFile) -> (Oid, String) {
+        let extension = file
+            .path
+            .extension()
+            .map(|ext| ext.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        (file.blob, extension)
+    }
+
+    fn get(&self, file: &TreeFile) -> impl Iterator<Item = (&String, &usize)> {
+        self.counts.get(&Self::key(file)).into_iter().flatten()
+    }
+
+    fn scan_missing(
+        &mut self,
+        scanner: &CodeDebtScanner,
+        repo: &Repository,
+        files: &[TreeFile],
+    ) -> Result<()> {
+        let mut keys = HashSet::new();
+        let missing: Vec<TreeFile> = files
+            .iter()
+            .filter(|file| {
+                let key = Self::key(file);
+                !self.counts.contains_key(&key) && keys.insert(key)
+            })
+            .cloned()
+            .collect();
+
+        let matcher = &scanner.matcher;
+        let options = &scanner.options;
+        let scanned = scan_blobs(repo, &missing, |file, bytes| {
+            let mut counts = BTreeMap::new();
+            if let FileScan::Scanned { items, .. } =
+                CodeDebtScanner::scan_bytes(&file.path, bytes, matcher, options)
+            {
+                for item in items {
+                    *counts.entry(item.pattern_type).or_insert(0) += 1;
+                }
+            }
+            counts
+        })?;
+        for (file, counts) in missing.iter().zip(scanned) {
+            self.counts.insert(Self::key(file), counts);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::commit;
+
+    #[test]
+    fn test_debt_history() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let first = commit(&repo, &[("a.rs", Some("// TODO: one\n"))], "Dev", 1_000);
+        commit(
+            &repo,
+            &[("b.rs", Some("// HACK: two\n// TODO: three\n"))],
+            "Dev",
+            2_000,
+        );
+        commit(&repo, &[("a.rs", Some("fn done() {}\n"))], "Dev", 3_000);
+        repo.tag_lightweight("v1", &repo.find_object(first, None).unwrap(), false)
+            .unwrap();
+
+        let scanner = CodeDebtScanner::new();
+        let totals = |options: HistoryOptions| {
+            debt_history(&scanner, &repo, &options)
+                .unwrap()
+                .iter()
+                .map(|point| point.total)
+                .collect::<Vec<_>>()
+        };
+
+        let points = debt_history(&scanner, &repo, &HistoryOptions::default()).unwrap();
+        assert_eq!(
+            points.iter().map(|p| p.total).collect::<Vec<_>>(),
+            vec![1, 3, 2]
+        );
+        assert_eq!(points[1].by_pattern.get("TODO"), Some(&2));
+        assert_eq!(points[1].by_severity.get(&Severity::Critical), Some(&1));
+        assert_eq!(points[2].date.timestamp(), 3_000);
+
+        let every_other = HistoryOptions {
+            sampling: Sampling::FirstParent { every: 2 },
+            ..Default::default()
+        };
+        assert_eq!(totals(every_other), vec![1, 2]);
+
+        let tags = HistoryOptions {
+            sampling: Sampling::Tags,
+            ..Default::default()
+        };
+        let points = debt_history(&scanner, &repo, &tags).unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].tag.as_deref(), Some("v1"));
+        assert_eq!(points[0].total, 1);
+
+        let latest = HistoryOptions {
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(totals(latest), vec![2]);
+    }
+}
@@ -231,6 +231,10 @@ impl InteractiveMode {
             println!("Pattern: {}", item.pattern_type.purple());
             println!("Severity: {:?}", item.severity);
 
+            if let Some(status) = item.blame_status {
+                println!("Blame: {}", status.as_str().yellow());
+            }
+
             if let Some(author) = &item.author {
                 println!("Author: {}", author.yellow());
             }
@@ -239,6 +243,18 @@ impl InteractiveMode {
                 println!("Age: {} days", age.to_string().yellow());
             }
 
+            if let Some(assignee) = &item.assignee {
+                println!("Assignee: {}", assignee.yellow());
+            }
+
+            if !item.issue_refs.is_empty() {
+                println!("Issues: {}", item.issue_refs.join(", ").cyan());
+            }
+
+            if let Some(due_date) = item.due_date {
+                println!("Due: {}", due_date.to_string().yellow());
+            }
+
             if item.duplicate_count > 1 {
                 println!("Duplicates: {}", item.duplicate_count.to_string().red());
             }
@@ -265,46 +281,43 @@ mod tests {
     fn create_test_items() -> Vec<CodeDebtItem> {
         vec![
             CodeDebtItem {
-                file_path: PathBuf::from("test1.rs"),
-                line_number: 10,
-                column: 5,
-                line_content: "// TODO: fix this".to_string(),
-                pattern_type: "TODO".to_string(),
-                severity: Severity::Medium,
-                author: None,
                 age_days: Some(5),
-                commit_hash: None,
-                created_at: None,
                 file_extension: Some("rs".to_string()),
                 duplicate_count: 1,
+                ..CodeDebtItem::new(
+                    PathBuf::from("test1.rs"),
+                    10,
+                    5,
+                    "// TODO: fix this",
+                    "TODO",
+                    Severity::Medium,
+                )
             },
             CodeDebtItem {
-                file_path: PathBuf::from("test2.rs"),
-                line_number: 20,
-                column: 3,
-                line_content: "// HACK: workaround".to_string(),
-                pattern_type: "HACK".to_string(),
-                severity: Severity::Critical,
-                author: None,
                 age_days: Some(10),
-                commit_hash: None,
-                created_at: None,
                 file_extension: Some("rs".to_string()),
                 duplicate_count: 1,
+                ..CodeDebtItem::new(
+                    PathBuf::from("test2.rs"),
+                    20,
+                    3,
+                    "// HACK: workaround",
+                    "HACK",
+                    Severity::Critical,
+                )
             },
             CodeDebtItem {
-                file_path: PathBuf::from("test3.rs"),
-                line_number: 30,
-                column: 1,
-                line_content: "// FIXME: broken".to_string(),
-                pattern_type: "FIXME".to_string(),
-                severity: Severity::High,
-                author: None,
                 age_days: Some(2),
-                commit_hash: None,
-                created_at: None,
                 file_extension: Some("rs".to_string()),
                 duplicate_count: 1,
+                ..CodeDebtItem::new(
+                    PathBuf::from("test3.rs"),
+                    30,
+                    1,
+                    "// FIXME: broken",
+                    "FIXME",
+                    Severity::High,
+                )
             },
         ]
     }
@@ -354,18 +367,17 @@ mod tests {
         // Add more items to test paging
         for i in 4..20 {
             items.push(CodeDebtItem {
-                file_path: PathBuf::from(format!("test{}.rs", i)),
-                line_number: i * 10,
-                column: 1,
-                line_content: "// TODO: item".to_string(),
-                pattern_type: "TODO".to_string(),
-                severity: Severity::Medium,
-                author: None,
                 age_days: Some(1),
-                commit_hash: None,
-                created_at: None,
                 file_extension: Some("rs".to_string()),
                 duplicate_count: 1,
+                ..CodeDebtItem::new(
+                    PathBuf::from(format!("test{}.rs", i)),
+                    i * 10,
+                    1,
+                    "// TODO: item",
+                    "TODO",
+                    Severity::Medium,
+                )
             });
         }
 
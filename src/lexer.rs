@@ -0,0 +1,422 @@
+use std::ops::Range;
+
+/// Comment and string literal syntax for a family of languages.
+#[derive(Debug)]
+pub struct Language {
+    pub name: &'static str,
+    pub line_comments: &'static [&'static str],
+    pub block_comments: &'static [(&'static str, &'static str)],
+    /// Whether block comments nest, as in Rust or Haskell
+    pub nested_blocks: bool,
+    /// String delimiters that may span several lines (checked first)
+    pub multiline_strings: &'static [&'static str],
+    /// String delimiters that end at the line break
+    pub strings: &'static [&'static str],
+    /// Triple-quoted strings treated as comments (Python docstrings)
+    pub doc_strings: &'static [&'static str],
+    /// Comment openers that mark documentation rather than a remark
+    pub doc_comments: &'static [&'static str],
+    /// Treat `'` as a char literal only when it closes, otherwise a lifetime
+    pub char_literals: bool,
+    /// Rust raw strings, `r"…"`, `r#"…"#`, `br"…"` and `cr"…"`, which have
+    /// no escapes
+    pub raw_strings: bool,
+}
+
+const RUST: Language = Language {
+    name: "rust",
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    nested_blocks: true,
+    multiline_strings: &["\""],
+    strings: &[],
+    doc_strings: &[],
+    doc_comments: &["///", "//!", "/**", "/*!"],
+    char_literals: true,
+    raw_strings: true,
+};
+
+const C_LIKE: Language = Language {
+    name: "c-like",
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    nested_blocks: false,
+    multiline_strings: &[],
+    strings: &["\"", "'"],
+    doc_strings: &[],
+    doc_comments: &["///", "/**"],
+    char_literals: false,
+    raw_strings: false,
+};
+
+const JAVASCRIPT: Language = Language {
+    name: "javascript",
+    multiline_strings: &["`"],
+    ..C_LIKE
+};
+
+const GO: Language = Language {
+    name: "go",
+    multiline_strings: &["`"],
+    ..C_LIKE
+};
+
+const SWIFT_LIKE: Language = Language {
+    name: "swift-like",
+    nested_blocks: true,
+    multiline_strings: &["\"\"\""],
+    ..C_LIKE
+};
+
+const ZIG: Language = Language {
+    name: "zig",
+    block_comments: &[],
+    ..C_LIKE
+};
+
+const PHP: Language = Language {
+    name: "php",
+    line_comments: &["//", "#"],
+    ..C_LIKE
+};
+
+const PYTHON: Language = Language {
+    name: "python",
+    line_comments: &["#"],
+    block_comments: &[],
+    nested_blocks: false,
+    multiline_strings: &[],
+    strings: &["\"", "'"],
+    doc_strings: &["\"\"\"", "'''"],
+    doc_comments: &[],
+    char_literals: false,
+    raw_strings: false,
+};
+
+const HASH: Language = Language {
+    name: "hash",
+    doc_strings: &[],
+    ..PYTHON
+};
+
+const NIM: Language = Language {
+    name: "nim",
+    block_comments: &[("#[", "]#")],
+    nested_blocks: true,
+    strings: &["\""],
+    ..HASH
+};
+
+const JULIA: Language = Language {
+    name: "julia",
+    block_comments: &[("#=", "=#")],
+    nested_blocks: true,
+    multiline_strings: &["\"\"\""],
+    strings: &["\""],
+    ..HASH
+};
+
+const LUA: Language = Language {
+    name: "lua",
+    line_comments: &["--"],
+    block_comments: &[("--[[", "]]")],
+    ..HASH
+};
+
+const HASKELL: Language = Language {
+    name: "haskell",
+    line_comments: &["--"],
+    block_comments: &[("{-", "-}")],
+    nested_blocks: true,
+    strings: &["\""],
+    ..HASH
+};
+
+const OCAML: Language = Language {
+    name: "ocaml",
+    line_comments: &[],
+    block_comments: &[("(*", "*)")],
+    nested_blocks: true,
+    strings: &["\""],
+    ..HASH
+};
+
+const CLOJURE: Language = Language {
+    name: "clojure",
+    line_comments: &[";"],
+    strings: &["\""],
+    ..HASH
+};
+
+impl Language {
+    pub fn for_extension(extension: &str) -> Option<&'static Language> {
+        let language = match extension.to_ascii_lowercase().as_str() {
+            "rs" => &RUST,
+            "c" | "cpp" | "cc" | "cxx" | "h" | "hpp" | "java" | "cs" | "v" => &C_LIKE,
+            "js" | "ts" | "jsx" | "tsx" | "mjs" | "cjs" => &JAVASCRIPT,
+            "go" => &GO,
+            "swift" | "kt" | "scala" | "dart" => &SWIFT_LIKE,
+            "zig" => &ZIG,
+            "php" => &PHP,
+            "py" => &PYTHON,
+            "rb" | "pl" | "r" | "cr" => &HASH,
+            "nim" => &NIM,
+            "jl" => &JULIA,
+            "lua" => &LUA,
+            "hs" | "elm" => &HASKELL,
+            "ml" => &OCAML,
+            "clj" => &CLOJURE,
+            _ => return None,
+        };
+        Some(language)
+    }
+
+    /// Byte ranges of all comments in `content`, in order.
+    pub fn comment_spans(&self, content: &str) -> Vec<Range<usize>> {
+        let bytes = content.as_bytes();
+        let mut spans = Vec::new();
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let rest = &bytes[i..];
+
+            if let Some((open, close)) = self
+                .block_comments
+                .iter()
+                .find(|(open, _)| rest.starts_with(open.as_bytes()))
+            {
+                let end = self.block_comment_end(bytes, i + open.len(), open, close);
+                spans.push(i..end);
+                i = end;
+            } else if self
+                .line_comments
+                .iter()
+                .any(|prefix| rest.starts_with(prefix.as_bytes()))
+            {
+                let end = find_line_end(bytes, i);
+                spans.push(i..end);
+                i = end;
+            } else if let Some(delim) = find_prefix(rest, self.doc_strings) {
+                let end = string_end(bytes, i + delim.len(), delim, true);
+                spans.push(i..end);
+                i = end;
+            } else if let Some(end) = self.raw_strings.then(|| raw_string_end(bytes, i)).flatten() {
+                i = end;
+            } else if let Some(delim) = find_prefix(rest, self.multiline_strings) {
+                i = string_end(bytes, i + delim.len(), delim, true);
+            } else if let Some(delim) = find_prefix(rest, self.strings) {
+                i = string_end(bytes, i + delim.len(), delim, false);
+            } else if self.char_literals && bytes[i] == b'\'' {
+                i = char_literal_end(content, i);
+            } else {
+                i += 1;
+            }
+        }
+
+        spans
+    }
+
+    /// Whether a comment from [`Language::comment_spans`] is documentation,
+    /// such as a Rust `///` comment or a Python docstring.
+    pub fn is_doc_comment(&self, comment: &str) -> bool {
+        self.doc_comments
+            .iter()
+            .chain(self.doc_strings)
+            .any(|prefix| comment.starts_with(prefix))
+    }
+
+    fn block_comment_end(&self, bytes: &[u8], start: usize, open: &str, close: &str) -> usize {
+        let mut depth = 1;
+        let mut i = start;
+        while i < bytes.len() {
+            let rest = &bytes[i..];
+            if self.nested_blocks && rest.starts_with(open.as_bytes()) {
+                depth += 1;
+                i += open.len();
+            } else if rest.starts_with(close.as_bytes()) {
+                depth -= 1;
+                i += close.len();
+                if depth == 0 {
+                    return i;
+                }
+            } else {
+                i += 1;
+            }
+        }
+        bytes.len()
+    }
+}
+
+/// Whether `offset` falls inside one of the sorted, non-overlapping `spans`.
+pub fn in_spans(spans: &[Range<usize>], offset: usize) -> bool {
+    let idx = spans.partition_point(|span| span.end <= offset);
+    spans.get(idx).is_some_and(|span| span.start <= offset)
+}
+
+fn find_prefix(rest: &[u8], delimiters: &'static [&'static str]) -> Option<&'static str> {
+    delimiters
+        .iter()
+        .copied()
+        .find(|delim| rest.starts_with(delim.as_bytes()))
+}
+
+fn find_line_end(bytes: &[u8], start: usize) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map(|pos| start + pos)
+        .unwrap_or(bytes.len())
+}
+
+fn string_end(bytes: &[u8], start: usize, delim: &str, multiline: bool) -> usize {
+    let mut i = start;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            i += 2;
+        } else if bytes[i..].starts_with(delim.as_bytes()) {
+            return i + delim.len();
+        } else if !multiline && bytes[i] == b'\n' {
+            // Unterminated literal, don't let it swallow the rest of the file
+            return i;
+        } else {
+            i += 1;
+        }
+    }
+    bytes.len()
+}
+
+/// End of the raw string starting at `start`, if there is one. The string
+/// is closed by a quote followed by as many `#` as it was opened with.
+fn raw_string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    // `r` in the middle of an identifier, e.g. `bar"`, doesn't start one
+    if start > 0 && (bytes[start - 1].is_ascii_alphanumeric() || bytes[start - 1] == b'_') {
+        return None;
+    }
+    let mut i = start;
+    if matches!(bytes.get(i), Some(b'b' | b'c')) {
+        i += 1;
+    }
+    if bytes.get(i) != Some(&b'r') {
+        return None;
+    }
+    let hashes = bytes[i + 1..].iter().take_while(|&&b| b == b'#').count();
+    i += 1 + hashes;
+    if bytes.get(i) != Some(&b'"') {
+        // A raw identifier such as `r#type`
+        return None;
+    }
+    i += 1;
+
+    let close = [b"\"".as_slice(), &b"#".repeat(hashes)].concat();
+    Some(
+        bytes[i..]
+            .windows(close.len())
+            .position(|window| window == close)
+            .map(|pos| i + pos + close.len())
+            .unwrap_or(bytes.len()),
+    )
+}
+
+fn char_literal_end(content: &str, quote: usize) -> usize {
+    let bytes = content.as_bytes();
+    match bytes.get(quote + 1) {
+        Some(b'\\') => bytes[quote + 2..]
+            .iter()
+            .take(12)
+            .position(|&b| b == b'\'')
+            .map(|pos| quote + 2 + pos + 1)
+            .unwrap_or(quote + 1),
+        Some(_) => {
+            let ch_len = content[quote + 1..]
+                .chars()
+                .next()
+                .map(char::len_utf8)
+                .unwrap_or(1);
+            if bytes.get(quote + 1 + ch_len) == Some(&b'\'') {
+                quote + ch_len + 2
+            } else {
+                // Lifetime or loop label
+                quote + 1
+            }
+        }
+        None => quote + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comments(ext: &str, content: &str) -> Vec<String> {
+        let language = Language::for_extension(ext).unwrap();
+        language
+            .comment_spans(content)
+            .into_iter()
+            .map(|span| content[span].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_line_comments_skip_strings() {
+        let found = comments("js", "let s = \"// not a comment\"; // real\n");
+        assert_eq!(found, vec!["// real"]);
+    }
+
+    #[test]
+    fn test_nested_rust_block_comments() {
+        let found = comments("rs", "/* outer /* inner */ still outer */ let x = 1;");
+        assert_eq!(found, vec!["/* outer /* inner */ still outer */"]);
+    }
+
+    #[test]
+    fn test_rust_lifetimes_and_chars() {
+        let found = comments("rs", "fn f<'a>(s: &'a str) -> char { '\"' } // done");
+        assert_eq!(found, vec!["// done"]);
+    }
+
+    #[test]
+    fn test_rust_raw_strings_have_no_escapes() {
+        let content = "let p = r\"C:\\\";\n// TODO: first\n\
+                       let q = r#\"say \"// hi\"\"#; let b = br\"\\\"; // FIXME: second\n\
+                       let r#type = 1; // third\n";
+        let found = comments("rs", content);
+        assert_eq!(
+            found,
+            vec!["// TODO: first", "// FIXME: second", "// third"]
+        );
+    }
+
+    #[test]
+    fn test_python_docstrings_are_comments() {
+        let content = "def f():\n    \"\"\"TODO: document\n    more\"\"\"\n    x = 'todo'\n";
+        let found = comments("py", content);
+        assert_eq!(found, vec!["\"\"\"TODO: document\n    more\"\"\""]);
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let rust = Language::for_extension("rs").unwrap();
+        assert!(rust.is_doc_comment("/// Docs"));
+        assert!(rust.is_doc_comment("/*! Crate docs */"));
+        assert!(!rust.is_doc_comment("// remark"));
+        let python = Language::for_extension("py").unwrap();
+        assert!(python.is_doc_comment("\"\"\"Docs\"\"\""));
+        assert!(!python.is_doc_comment("# remark"));
+    }
+
+    #[test]
+    fn test_unknown_extension() {
+        assert!(Language::for_extension("txt").is_none());
+    }
+
+    #[test]
+    fn test_in_spans() {
+        let spans = vec![2..5, 10..12];
+        assert!(!in_spans(&spans, 1));
+        assert!(in_spans(&spans, 2));
+        assert!(in_spans(&spans, 4));
+        assert!(!in_spans(&spans, 5));
+        assert!(in_spans(&spans, 11));
+        assert!(!in_spans(&spans, 12));
+    }
+}
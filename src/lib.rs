@@ -1,15 +1,35 @@
+pub mod annotation;
+pub mod baseline;
+pub mod blame;
+pub mod config;
+pub mod diff;
+pub mod encoding;
 pub mod error;
+pub mod fingerprint;
 pub mod git;
+pub mod history;
 pub mod interactive;
+pub mod lexer;
+pub mod matcher;
 pub mod models;
 pub mod patterns;
 pub mod progress;
+pub mod report;
+pub mod resolution;
+pub mod revision;
+pub mod sarif;
 pub mod scanner;
+pub mod suppression;
+#[cfg(test)]
+mod test_support;
+pub mod thresholds;
 pub mod watch;
 
-pub use models::{CodeDebtItem, Severity};
+pub use config::Config;
+pub use models::{BlameStatus, CodeDebtItem, Severity};
 pub use patterns::Pattern;
-pub use scanner::CodeDebtScanner;
+pub use report::ScanReport;
+pub use scanner::{CodeDebtScanner, ScanOptions};
 
 #[cfg(test)]
 mod tests {
@@ -30,7 +50,7 @@ mod tests {
     #[test]
     fn test_scanner_creation() {
         let scanner = CodeDebtScanner::new();
-        assert!(!scanner.patterns.is_empty());
+        assert!(!scanner.patterns().is_empty());
         assert!(!scanner.file_extensions.is_empty());
         assert!(!scanner.ignore_dirs.is_empty());
     }
@@ -38,7 +58,8 @@ mod tests {
     #[test]
     fn test_default_patterns() {
         let scanner = CodeDebtScanner::new();
-        let pattern_names: Vec<String> = scanner.patterns.iter().map(|p| p.name.clone()).collect();
+        let pattern_names: Vec<String> =
+            scanner.patterns().iter().map(|p| p.name.clone()).collect();
 
         assert!(pattern_names.contains(&"TODO".to_string()));
         assert!(pattern_names.contains(&"FIXME".to_string()));
@@ -62,7 +83,12 @@ fn main() {
 
         let scanner = CodeDebtScanner::new();
         let file_path = Path::new("test.rs");
-        let items = CodeDebtScanner::scan_content(file_path, test_content, &scanner.patterns);
+        let items = CodeDebtScanner::scan_content(
+            file_path,
+            test_content,
+            &scanner.matcher,
+            &scanner.options,
+        );
 
         assert_eq!(items.len(), 3);
 
@@ -98,9 +124,15 @@ const API_KEY = "placeholder for production";
 let temp_production_fix = true;
 "#;
 
-        let scanner = CodeDebtScanner::new();
+        // The debt is in code rather than comments here
+        let scanner = CodeDebtScanner::new().with_comments_only(false);
         let file_path = Path::new("test.js");
-        let items = CodeDebtScanner::scan_content(file_path, test_content, &scanner.patterns);
+        let items = CodeDebtScanner::scan_content(
+            file_path,
+            test_content,
+            &scanner.matcher,
+            &scanner.options,
+        );
 
         let production_debt = items
             .iter()
@@ -118,8 +150,8 @@ let temp_production_fix = true;
         }];
 
         let scanner = CodeDebtScanner::new().with_patterns(custom_patterns);
-        assert_eq!(scanner.patterns.len(), 1);
-        assert_eq!(scanner.patterns[0].name, "URGENT");
+        assert_eq!(scanner.patterns().len(), 1);
+        assert_eq!(scanner.patterns()[0].name, "URGENT");
     }
 
     #[test]
@@ -143,48 +175,30 @@ let temp_production_fix = true;
     #[test]
     fn test_get_summary() {
         let items = vec![
-            CodeDebtItem {
-                file_path: PathBuf::from("test.rs"),
-                line_number: 1,
-                column: 1,
-                line_content: "// TODO: test".to_string(),
-                pattern_type: "TODO".to_string(),
-                severity: Severity::Medium,
-                author: None,
-                age_days: None,
-                commit_hash: None,
-                created_at: None,
-                file_extension: None,
-                duplicate_count: 0,
-            },
-            CodeDebtItem {
-                file_path: PathBuf::from("test.rs"),
-                line_number: 2,
-                column: 1,
-                line_content: "// TODO: another test".to_string(),
-                pattern_type: "TODO".to_string(),
-                severity: Severity::Medium,
-                author: None,
-                age_days: None,
-                commit_hash: None,
-                created_at: None,
-                file_extension: None,
-                duplicate_count: 0,
-            },
-            CodeDebtItem {
-                file_path: PathBuf::from("test.rs"),
-                line_number: 3,
-                column: 1,
-                line_content: "// FIXME: broken".to_string(),
-                pattern_type: "FIXME".to_string(),
-                severity: Severity::High,
-                author: None,
-                age_days: None,
-                commit_hash: None,
-                created_at: None,
-                file_extension: None,
-                duplicate_count: 0,
-            },
+            CodeDebtItem::new(
+                PathBuf::from("test.rs"),
+                1,
+                1,
+                "// TODO: test",
+                "TODO",
+                Severity::Medium,
+            ),
+            CodeDebtItem::new(
+                PathBuf::from("test.rs"),
+                2,
+                1,
+                "// TODO: another test",
+                "TODO",
+                Severity::Medium,
+            ),
+            CodeDebtItem::new(
+                PathBuf::from("test.rs"),
+                3,
+                1,
+                "// FIXME: broken",
+                "FIXME",
+                Severity::High,
+            ),
         ];
 
         let scanner = CodeDebtScanner::new();
@@ -197,48 +211,30 @@ let temp_production_fix = true;
     #[test]
     fn test_filter_by_severity() {
         let items = vec![
-            CodeDebtItem {
-                file_path: PathBuf::from("test.rs"),
-                line_number: 1,
-                column: 1,
-                line_content: "// TODO: test".to_string(),
-                pattern_type: "TODO".to_string(),
-                severity: Severity::Medium,
-                author: None,
-                age_days: None,
-                commit_hash: None,
-                created_at: None,
-                file_extension: None,
-                duplicate_count: 0,
-            },
-            CodeDebtItem {
-                file_path: PathBuf::from("test.rs"),
-                line_number: 2,
-                column: 1,
-                line_content: "// HACK: critical".to_string(),
-                pattern_type: "HACK".to_string(),
-                severity: Severity::Critical,
-                author: None,
-                age_days: None,
-                commit_hash: None,
-                created_at: None,
-                file_extension: None,
-                duplicate_count: 0,
-            },
-            CodeDebtItem {
-                file_path: PathBuf::from("test.rs"),
-                line_number: 3,
-                column: 1,
-                line_content: "// mock data".to_string(),
-                pattern_type: "MOCK_STUB".to_string(),
-                severity: Severity::Low,
-                author: None,
-                age_days: None,
-                commit_hash: None,
-                created_at: None,
-                file_extension: None,
-                duplicate_count: 0,
-            },
+            CodeDebtItem::new(
+                PathBuf::from("test.rs"),
+                1,
+                1,
+                "// TODO: test",
+                "TODO",
+                Severity::Medium,
+            ),
+            CodeDebtItem::new(
+                PathBuf::from("test.rs"),
+                2,
+                1,
+                "// HACK: critical",
+                "HACK",
+                Severity::Critical,
+            ),
+            CodeDebtItem::new(
+                PathBuf::from("test.rs"),
+                3,
+                1,
+                "// mock data",
+                "MOCK_STUB",
+                Severity::Low,
+            ),
         ];
 
         let scanner = CodeDebtScanner::new();
@@ -288,6 +284,110 @@ let temp_production_fix = true;
         }
     }
 
+    #[test]
+    fn test_scan_skips_ignored_and_excluded_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        for dir in ["src", "vendor", "generated", "src/proto"] {
+            fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        }
+        create_test_file(temp_dir.path(), "src/lib.rs", "// TODO: keep");
+        create_test_file(temp_dir.path(), "src/lib_test.rs", "// TODO: test");
+        create_test_file(temp_dir.path(), "vendor/dep.rs", "// TODO: vendored");
+        create_test_file(temp_dir.path(), "generated/api.rs", "// TODO: generated");
+        create_test_file(temp_dir.path(), "src/proto/msg.rs", "// TODO: proto");
+        create_test_file(temp_dir.path(), ".codedebtignore", "generated/\n");
+
+        let scan = |scanner: CodeDebtScanner| {
+            let mut names: Vec<String> = scanner
+                .scan(temp_dir.path())
+                .unwrap()
+                .iter()
+                .map(|item| item.file_path.file_name().unwrap().to_string_lossy().into())
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(
+            scan(CodeDebtScanner::new()),
+            vec!["lib.rs", "lib_test.rs", "msg.rs"]
+        );
+        assert_eq!(
+            scan(CodeDebtScanner::new().with_exclude(vec!["*_test.rs".into(), "proto/".into()])),
+            vec!["lib.rs"]
+        );
+        assert_eq!(
+            scan(
+                CodeDebtScanner::new()
+                    .with_include(vec!["src/**".into()])
+                    .with_exclude(vec!["src/proto".into()])
+            ),
+            vec!["lib.rs", "lib_test.rs"]
+        );
+    }
+
+    #[test]
+    fn test_scan_files_and_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let lib = create_test_file(temp_dir.path(), "lib.rs", "// TODO: one");
+        let notes = create_test_file(temp_dir.path(), "notes.txt", "TODO: explicit");
+        create_test_file(temp_dir.path(), "other.rs", "// FIXME: not asked for");
+
+        let scanner = CodeDebtScanner::new();
+        let items = scanner.scan_files(&[lib, notes]).unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| item.pattern_type == "TODO"));
+
+        assert!(scanner
+            .scan_files(&[temp_dir.path().join("missing.rs")])
+            .is_err());
+
+        let items = scanner.scan_source("buffer.py", "x = 1  # HACK: unsaved\n");
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].file_path, PathBuf::from("buffer.py"));
+        assert_eq!(items[0].file_extension.as_deref(), Some("py"));
+    }
+
+    #[test]
+    fn test_scan_report_counts_scanned_and_skipped_files() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_file(temp_dir.path(), "lib.rs", "// TODO: one\n");
+        create_test_file(temp_dir.path(), "clean.rs", "fn main() {}\n");
+        fs::write(temp_dir.path().join("latin1.rs"), b"// TODO: caf\xe9\n").unwrap();
+        fs::write(temp_dir.path().join("blob.rs"), b"\x00\x01// TODO\xff").unwrap();
+
+        let scanner = CodeDebtScanner::new();
+        let report = scanner.scan_report(temp_dir.path()).unwrap();
+        assert_eq!(report.items.len(), 2);
+        assert_eq!(report.files_scanned, 3);
+        assert_eq!(report.bytes_read, 40);
+        assert_eq!(report.files_skipped.len(), 1);
+        assert_eq!(
+            report.files_skipped[0].path,
+            temp_dir.path().join("blob.rs")
+        );
+        assert_eq!(
+            report.files_skipped[0].reason,
+            crate::report::SkipReason::Binary
+        );
+        assert_eq!(
+            report.files_transcoded[0].encoding,
+            crate::encoding::Encoding::Windows1252
+        );
+        assert_eq!(report.config_hash, scanner.config_hash());
+        assert_ne!(
+            report.config_hash,
+            CodeDebtScanner::new()
+                .with_comments_only(false)
+                .config_hash()
+        );
+
+        let error = scanner
+            .scan_files(&[temp_dir.path().join("blob.rs")])
+            .unwrap_err();
+        assert!(error.to_string().contains("binary"));
+    }
+
     #[test]
     fn test_severity_ordering() {
         assert!(Severity::Critical < Severity::High);
@@ -307,7 +407,12 @@ let temp_production_fix = true;
 
         let scanner = CodeDebtScanner::new();
         let file_path = Path::new("test.rs");
-        let items = CodeDebtScanner::scan_content(file_path, test_content, &scanner.patterns);
+        let items = CodeDebtScanner::scan_content(
+            file_path,
+            test_content,
+            &scanner.matcher,
+            &scanner.options,
+        );
 
         let todo_items: Vec<_> = items
             .iter()
@@ -321,4 +426,181 @@ let temp_production_fix = true;
         assert_eq!(todo_items.len(), 3); // All variations of TODO
         assert_eq!(fixme_items.len(), 2); // All variations of FIXME
     }
+
+    #[test]
+    fn test_comments_only_skips_code_and_strings() {
+        let test_content = r#"
+let temp = load_mock_server();
+let msg = "todo list";
+// TODO: real debt
+/* FIXME: also /* nested */ real */ let stub = 1;
+"#;
+
+        let scanner = CodeDebtScanner::new();
+        let items = CodeDebtScanner::scan_content(
+            Path::new("test.rs"),
+            test_content,
+            &scanner.matcher,
+            &scanner.options,
+        );
+
+        let found: Vec<_> = items.iter().map(|i| i.pattern_type.as_str()).collect();
+        assert_eq!(found, vec!["TODO", "FIXME"]);
+
+        let scanner = CodeDebtScanner::new().with_comments_only(false);
+        let items = CodeDebtScanner::scan_content(
+            Path::new("test.rs"),
+            test_content,
+            &scanner.matcher,
+            &scanner.options,
+        );
+        assert!(items.iter().any(|i| i.pattern_type == "TEMPORARY"));
+        assert!(items.iter().any(|i| i.pattern_type == "MOCK_STUB"));
+    }
+
+    #[test]
+    fn test_comments_only_unknown_language_matches_everything() {
+        let scanner = CodeDebtScanner::new();
+        let items = CodeDebtScanner::scan_content(
+            Path::new("notes.txt"),
+            "temp value\n",
+            &scanner.matcher,
+            &scanner.options,
+        );
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].pattern_type, "TEMPORARY");
+    }
+
+    #[test]
+    fn test_match_spans_and_all_matches() {
+        let test_content = "let s = 1; // café TODO: a, todo: b\n// FIXME\n";
+
+        let scanner = CodeDebtScanner::new();
+        let items = CodeDebtScanner::scan_content(
+            Path::new("test.rs"),
+            test_content,
+            &scanner.matcher,
+            &scanner.options,
+        );
+        assert_eq!(items.len(), 2);
+        // Columns count characters, offsets count bytes
+        assert_eq!(items[0].column, 20);
+        assert_eq!(items[0].end_column, 24);
+        assert_eq!(items[0].byte_offset, 20);
+        assert_eq!(items[0].matched_text, "TODO");
+        assert_eq!(items[1].byte_offset, 40);
+        assert_eq!(items[1].matched_text, "FIXME");
+
+        let scanner = CodeDebtScanner::new().with_all_matches(true);
+        let items = CodeDebtScanner::scan_content(
+            Path::new("test.rs"),
+            test_content,
+            &scanner.matcher,
+            &scanner.options,
+        );
+        let todos: Vec<_> = items
+            .iter()
+            .filter(|i| i.pattern_type == "TODO")
+            .map(|i| (i.column, i.byte_offset, i.matched_text.as_str()))
+            .collect();
+        assert_eq!(todos, vec![(20, 20, "TODO"), (29, 29, "todo")]);
+        assert_eq!(items[1].message.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_byte_offsets_count_the_bom() {
+        let temp_dir = TempDir::new().unwrap();
+        let bytes = b"\xEF\xBB\xBF// caf\xc3\xa9\n// TODO: bom\n";
+        fs::write(temp_dir.path().join("bom.rs"), bytes).unwrap();
+
+        let items = CodeDebtScanner::new().scan(temp_dir.path()).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].column, 4);
+        assert_eq!(&bytes[items[0].byte_offset..][..4], b"TODO");
+    }
+
+    #[test]
+    fn test_structured_annotations() {
+        let test_content = r#"
+// TODO(alice): handle errors
+// FIXME(#1234)
+// HACK[2025-03-01] remove after migration
+"#;
+
+        let scanner = CodeDebtScanner::new();
+        let items = CodeDebtScanner::scan_content(
+            Path::new("test.rs"),
+            test_content,
+            &scanner.matcher,
+            &scanner.options,
+        );
+        assert_eq!(items.len(), 3);
+
+        assert_eq!(items[0].assignee.as_deref(), Some("alice"));
+        assert_eq!(items[0].message.as_deref(), Some("handle errors"));
+        assert_eq!(items[1].issue_refs, vec!["#1234"]);
+        assert_eq!(
+            items[2].due_date,
+            chrono::NaiveDate::from_ymd_opt(2025, 3, 1)
+        );
+
+        assert_eq!(scanner.filter_by_assignee(&items, "ALICE").len(), 1);
+        assert_eq!(scanner.filter_by_issue(&items, "#1234").len(), 1);
+        let cutoff = chrono::NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+        assert_eq!(scanner.filter_due_before(&items, cutoff).len(), 1);
+    }
+
+    #[test]
+    fn test_suppression_directives() {
+        let test_content = r#"
+// HACK: documented workaround codedebt:ignore
+// codedebt:ignore-next-line FIXME
+// FIXME: TODO: both here
+// TODO: still reported
+"#;
+
+        let scanner = CodeDebtScanner::new();
+        let items = CodeDebtScanner::scan_content(
+            Path::new("test.rs"),
+            test_content,
+            &scanner.matcher,
+            &scanner.options,
+        );
+        let mut found: Vec<_> = items
+            .iter()
+            .map(|i| (i.line_number, i.pattern_type.as_str()))
+            .collect();
+        found.sort();
+        assert_eq!(found, vec![(4, "TODO"), (5, "TODO")]);
+
+        let scanner = CodeDebtScanner::new().with_suppressed(true);
+        let items = CodeDebtScanner::scan_content(
+            Path::new("test.rs"),
+            test_content,
+            &scanner.matcher,
+            &scanner.options,
+        );
+        let suppressed: Vec<_> = items.iter().filter(|i| i.suppressed).collect();
+        assert_eq!(suppressed.len(), 3);
+
+        let file_ignored = CodeDebtScanner::scan_content(
+            Path::new("test.rs"),
+            "// codedebt:ignore-file\n// TODO: hidden\n",
+            &scanner.matcher,
+            &CodeDebtScanner::new().options,
+        );
+        assert!(file_ignored.is_empty());
+
+        // Directives quoted in strings or documentation are not directives
+        let quoted = CodeDebtScanner::scan_content(
+            Path::new("test.rs"),
+            "let s = \"// codedebt:ignore-file\";\n\
+             /// `codedebt:ignore-next-line`\n\
+             // TODO: shown\n",
+            &scanner.matcher,
+            &scanner.options,
+        );
+        assert_eq!(quoted.len(), 1);
+        assert!(!quoted[0].suppressed);
+    }
 }
@@ -0,0 +1,290 @@
+use crate::patterns::Pattern;
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, Input};
+use regex::RegexSet;
+use regex_syntax::hir::{Class, Hir, HirKind};
+
+/// More literals than this per pattern are not worth prefiltering on.
+const MAX_LITERALS: usize = 64;
+/// Larger character classes end a literal, e.g. `[a-z]`.
+const MAX_CLASS_CHARS: usize = 8;
+
+/// All patterns of a scanner compiled once: a `RegexSet` telling which
+/// patterns match a line, and an Aho-Corasick prefilter over the literals
+/// every match must start with, run once over a whole file, so lines that
+/// can't match are skipped without running any regex.
+#[derive(Debug, Clone)]
+pub struct PatternMatcher {
+    patterns: Vec<Pattern>,
+    set: Option<RegexSet>,
+    /// `None` if some pattern has no required literal, e.g. `\w+`
+    prefilter: Option<AhoCorasick>,
+}
+
+impl PatternMatcher {
+    pub fn new(patterns: Vec<Pattern>) -> Self {
+        let set = RegexSet::new(patterns.iter().map(|p| p.regex.as_str())).ok();
+        let prefilter = patterns
+            .iter()
+            .map(required_literals)
+            .collect::<Option<Vec<Vec<String>>>>()
+            .and_then(|literals| {
+                AhoCorasickBuilder::new()
+                    .ascii_case_insensitive(true)
+                    .build(literals.into_iter().flatten())
+                    .ok()
+            });
+        Self {
+            patterns,
+            set,
+            prefilter,
+        }
+    }
+
+    /// A matcher that runs every pattern on every line, as a reference for
+    /// the compiled one.
+    pub fn naive(patterns: Vec<Pattern>) -> Self {
+        Self {
+            patterns,
+            set: None,
+            prefilter: None,
+        }
+    }
+
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    /// Whether lines are prefiltered, i.e. every pattern has a literal that
+    /// all its matches start with.
+    pub fn has_prefilter(&self) -> bool {
+        self.prefilter.is_some()
+    }
+
+    /// Byte offsets of the starts of lines in `content` that may match, in
+    /// order, or `None` if every line has to be checked.
+    pub(crate) fn candidate_lines(&self, content: &str) -> Option<Vec<usize>> {
+        let prefilter = self.prefilter.as_ref()?;
+        let mut lines = Vec::new();
+        let mut pos = 0;
+        while let Some(m) = prefilter.find(Input::new(content).span(pos..content.len())) {
+            let start = content[..m.start()].rfind('\n').map_or(0, |i| i + 1);
+            lines.push(start);
+            // One hit is enough, continue on the next line
+            match content[m.start()..].find('\n') {
+                Some(i) => pos = m.start() + i + 1,
+                None => break,
+            }
+        }
+        Some(lines)
+    }
+
+    /// The patterns matching somewhere in `line`, in order.
+    pub(crate) fn matching<'a>(&'a self, line: &str) -> Box<dyn Iterator<Item = &'a Pattern> + 'a> {
+        match &self.set {
+            Some(set) => Box::new(
+                set.matches(line)
+                    .into_iter()
+                    .map(move |idx| &self.patterns[idx]),
+            ),
+            None => Box::new(self.patterns.iter()),
+        }
+    }
+}
+
+/// Literals, lowercased, one of which every match of `pattern` starts with,
+/// or `None` if there is no such set.
+fn required_literals(pattern: &Pattern) -> Option<Vec<String>> {
+    let hir = regex_syntax::Parser::new()
+        .parse(pattern.regex.as_str())
+        .ok()?;
+    let (literals, _) = prefixes(&hir)?;
+    if literals.iter().any(String::is_empty) {
+        return None;
+    }
+    Some(literals)
+}
+
+/// The prefixes every match of `hir` starts with, and whether they are the
+/// whole match so a following expression can extend them.
+fn prefixes(hir: &Hir) -> Option<(Vec<String>, bool)> {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => Some((vec![String::new()], true)),
+        HirKind::Literal(literal) => {
+            let text = std::str::from_utf8(&literal.0).ok()?;
+            Some((vec![text.to_ascii_lowercase()], true))
+        }
+        HirKind::Class(Class::Unicode(class)) => {
+            let mut literals = Vec::new();
+            for range in class.ranges() {
+                for c in range.start()..=range.end() {
+                    if literals.len() == MAX_CLASS_CHARS {
+                        return None;
+                    }
+                    literals.push(c.to_ascii_lowercase().to_string());
+                }
+            }
+            literals.sort();
+            literals.dedup();
+            Some((literals, true))
+        }
+        HirKind::Class(Class::Bytes(_)) => None,
+        HirKind::Capture(capture) => prefixes(&capture.sub),
+        HirKind::Repetition(repetition) if repetition.min == 0 => {
+            Some((vec![String::new()], false))
+        }
+        HirKind::Repetition(repetition) => {
+            let (literals, _) = prefixes(&repetition.sub)?;
+            Some((literals, false))
+        }
+        HirKind::Concat(subs) => {
+            let mut acc = vec![String::new()];
+            for sub in subs {
+                let Some((literals, exact)) = prefixes(sub) else {
+                    return Some((acc, false));
+                };
+                if acc.len() * literals.len() > MAX_LITERALS {
+                    return Some((acc, false));
+                }
+                acc = acc
+                    .iter()
+                    .flat_map(|prefix| literals.iter().map(move |lit| format!("{prefix}{lit}")))
+                    .collect();
+                if !exact {
+                    return Some((acc, false));
+                }
+            }
+            Some((acc, true))
+        }
+        HirKind::Alternation(subs) => {
+            let mut all = Vec::new();
+            let mut all_exact = true;
+            for sub in subs {
+                let (literals, exact) = prefixes(sub)?;
+                all.extend(literals);
+                all_exact &= exact;
+            }
+            all.sort();
+            all.dedup();
+            (all.len() <= MAX_LITERALS).then_some((all, all_exact))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CodeDebtItem, Severity};
+    use crate::scanner::{CodeDebtScanner, ScanOptions};
+    use std::path::Path;
+
+    fn literals(regex: &str) -> Option<Vec<String>> {
+        required_literals(&Pattern::new("P".into(), regex, Severity::Low).unwrap())
+    }
+
+    #[test]
+    fn test_required_literals() {
+        assert_eq!(literals(r"(?i)\bFIXME\b"), Some(vec!["fixme".into()]));
+        // Case folding maps k to the Kelvin sign too
+        assert_eq!(
+            literals(r"(?i)\b(HACK|XXX)\b"),
+            Some(vec!["hack".into(), "hac\u{212A}".into(), "xxx".into()])
+        );
+        assert_eq!(literals(r"(?i)\bNOTE.*fix\b"), Some(vec!["note".into()]));
+        assert_eq!(literals(r"TODO\(\w+\)"), Some(vec!["todo(".into()]));
+        assert_eq!(literals(r"\w+:"), None);
+        assert_eq!(literals(r"(?i)(?:fix)?me"), None);
+    }
+
+    /// Source files of several languages with one line of debt in every
+    /// `debt_every` lines, including matches in strings, suppressions,
+    /// non-ASCII case folding and near misses the prefilter lets through.
+    fn corpus(files: usize, debt_every: usize) -> Vec<(String, String)> {
+        let code = [
+            "    let total = values.iter().map(|v| v * 2).sum::<u64>();\n",
+            "        if index >= self.len() { return None; }\n",
+        ];
+        let debt = [
+            ("rs", "    // TODO(alice, #12): handle overflow\n"),
+            (
+                "rs",
+                "    let temperature = sensor.read()?; // stubborn hardware\n",
+            ),
+            (
+                "rs",
+                "    let s = \"HACK in a string\"; // FIXME: not this one\n",
+            ),
+            ("py", "    result = compute(a, b)  # temporary workaround\n"),
+            (
+                "py",
+                "    \"\"\"Docstring with a mock and NOTE to fix\"\"\"\n",
+            ),
+            (
+                "js",
+                "  const stubbed = mocking(placeholderValue); // XXX\n",
+            ),
+            (
+                "js",
+                "  // codedebt:ignore-next-line\n  // TODO: suppressed\n",
+            ),
+            ("c", "    /* placeholder until production config lands */\n"),
+            (
+                "txt",
+                "TODO: plain text matches everywhere, ha\u{212A} too\n",
+            ),
+            ("go", "\t// Mo\u{212A}: Kelvin sign folds to k\n"),
+        ];
+        (0..files)
+            .map(|file| {
+                let (ext, _) = debt[file % debt.len()];
+                let mut content = String::new();
+                for line in 0..400 {
+                    let index = file * 400 + line;
+                    content.push_str(if index % debt_every == 0 {
+                        debt[index / debt_every % debt.len()].1
+                    } else {
+                        code[index % code.len()]
+                    });
+                }
+                (format!("src/file{}.{}", file, ext), content)
+            })
+            .collect()
+    }
+
+    fn scan_corpus(
+        corpus: &[(String, String)],
+        matcher: &PatternMatcher,
+        options: &ScanOptions,
+    ) -> Vec<CodeDebtItem> {
+        corpus
+            .iter()
+            .flat_map(|(path, content)| {
+                CodeDebtScanner::scan_content(Path::new(path), content, matcher, options)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compiled_matcher_matches_naive() {
+        let compiled = PatternMatcher::new(Pattern::default_patterns());
+        let naive = PatternMatcher::naive(Pattern::default_patterns());
+        assert!(compiled.has_prefilter());
+        let corpus = corpus(60, 37);
+
+        for options in [
+            ScanOptions::default(),
+            ScanOptions {
+                comments_only: false,
+                include_suppressed: true,
+                all_matches: true,
+            },
+        ] {
+            let expected = scan_corpus(&corpus, &naive, &options);
+            assert!(!expected.is_empty());
+            let actual = scan_corpus(&corpus, &compiled, &options);
+            assert_eq!(
+                serde_json::to_value(&actual).unwrap(),
+                serde_json::to_value(&expected).unwrap()
+            );
+        }
+    }
+}
@@ -1,17 +1,35 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeDebtItem {
     pub file_path: PathBuf,
     pub line_number: usize,
+    /// 1-based column of the match, counted in characters
     pub column: usize,
+    /// Column just past the end of the match
+    #[serde(default)]
+    pub end_column: usize,
+    /// Byte offset of the match from the start of the file as stored,
+    /// counting any byte order mark, see [`crate::encoding::source_offsets`]
+    #[serde(default)]
+    pub byte_offset: usize,
+    #[serde(default)]
+    pub matched_text: String,
     pub line_content: String,
     pub pattern_type: String,
     pub severity: Severity,
+    /// Identity within a file that survives line shifts, see
+    /// [`crate::fingerprint::fingerprint`]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub fingerprint: String,
 
     // Enhanced intelligence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blame_status: Option<BlameStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub author: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -20,15 +38,159 @@ pub struct CodeDebtItem {
     pub commit_hash: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created_at: Option<DateTime<Utc>>,
+    /// Working directory of the repository the file belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo_path: Option<PathBuf>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub file_extension: Option<String>,
     pub duplicate_count: usize,
+
+    // Structured annotation, e.g. `TODO(alice, #12): message`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issue_refs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// Silenced by a `codedebt:ignore` directive
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suppressed: bool,
+}
+
+impl CodeDebtItem {
+    pub fn new(
+        file_path: impl Into<PathBuf>,
+        line_number: usize,
+        column: usize,
+        line_content: impl Into<String>,
+        pattern_type: impl Into<String>,
+        severity: Severity,
+    ) -> Self {
+        Self {
+            file_path: file_path.into(),
+            line_number,
+            column,
+            end_column: column,
+            byte_offset: 0,
+            matched_text: String::new(),
+            line_content: line_content.into(),
+            pattern_type: pattern_type.into(),
+            severity,
+            fingerprint: String::new(),
+            blame_status: None,
+            author: None,
+            age_days: None,
+            commit_hash: None,
+            created_at: None,
+            repo_path: None,
+            file_extension: None,
+            duplicate_count: 0,
+            assignee: None,
+            issue_refs: Vec::new(),
+            due_date: None,
+            message: None,
+            suppressed: false,
+        }
+    }
+}
+
+/// Where an item's blame information came from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum BlameStatus {
+    /// Blamed to the commit that last changed the line
+    Committed,
+    /// The line differs from HEAD in the working tree
+    Uncommitted,
+    /// The file is not tracked by git
+    Untracked,
+    /// The file is not inside a git repository
+    OutsideRepo,
+    /// Blame failed
+    Error,
+}
+
+impl BlameStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlameStatus::Committed => "committed",
+            BlameStatus::Uncommitted => "uncommitted",
+            BlameStatus::Untracked => "untracked",
+            BlameStatus::OutsideRepo => "outside-repo",
+            BlameStatus::Error => "error",
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Severity {
+    #[serde(alias = "critical")]
     Critical,
+    #[serde(alias = "high")]
     High,
+    #[serde(alias = "medium")]
     Medium,
+    #[serde(alias = "low")]
     Low,
 }
+
+impl Severity {
+    /// How much an item of this severity counts towards a hotspot's debt score
+    pub fn weight(&self) -> usize {
+        match self {
+            Severity::Critical => 8,
+            Severity::High => 4,
+            Severity::Medium => 2,
+            Severity::Low => 1,
+        }
+    }
+}
+
+/// How much a file changed in a time window.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FileChurn {
+    /// Non-merge commits touching the file
+    pub commits: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+impl FileChurn {
+    pub fn lines_changed(&self) -> usize {
+        self.lines_added + self.lines_removed
+    }
+}
+
+/// A file that is both full of debt and frequently changed.
+#[derive(Debug, Clone, Serialize)]
+pub struct Hotspot {
+    pub file_path: PathBuf,
+    pub items: usize,
+    pub by_severity: BTreeMap<Severity, usize>,
+    /// Items weighted by severity, see [`Severity::weight`]
+    pub debt_score: usize,
+    #[serde(flatten)]
+    pub churn: FileChurn,
+    /// Debt score times the number of commits
+    pub score: usize,
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "critical" => Ok(Severity::Critical),
+            "high" => Ok(Severity::High),
+            "medium" => Ok(Severity::Medium),
+            "low" => Ok(Severity::Low),
+            other => Err(format!(
+                "unknown severity '{}', expected critical, high, medium or low",
+                other
+            )),
+        }
+    }
+}
@@ -1,53 +1,275 @@
-use colored::*;
-use std::io::{self, Write};
+use indicatif::{HumanBytes, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+/// Receives progress of a scan. Files are reported from the worker threads
+/// as they finish, in no particular order.
 pub trait ProgressReporter: Send + Sync {
-    fn start(&self, total: usize);
-    fn update(&self, current: usize);
+    /// Called once the files to scan are known, before any is read
+    fn start(&self, total_files: usize, total_bytes: u64);
+    /// Called after each file is scanned
+    fn file_scanned(&self, path: &Path, bytes: u64);
     fn finish(&self);
 }
 
+/// An indicatif progress bar on stderr with files/sec, bytes scanned and
+/// ETA. Hidden when stderr is not a terminal, so redirected output and CI
+/// logs stay clean.
 pub struct TerminalProgressReporter {
-    total: std::sync::Mutex<usize>,
-    show_progress: bool,
+    bar: ProgressBar,
+    bytes: Arc<AtomicU64>,
+    total_bytes: Arc<AtomicU64>,
 }
 
 impl TerminalProgressReporter {
     pub fn new(show_progress: bool) -> Self {
+        let target = if show_progress && io::stderr().is_terminal() {
+            ProgressDrawTarget::stderr()
+        } else {
+            ProgressDrawTarget::hidden()
+        };
+        let bytes = Arc::new(AtomicU64::new(0));
+        let total_bytes = Arc::new(AtomicU64::new(0));
+
+        let (scanned, total) = (Arc::clone(&bytes), Arc::clone(&total_bytes));
+        let style = ProgressStyle::with_template(
+            "{spinner:.cyan} Scanning [{bar:30.cyan/blue}] {pos}/{len} files ({per_sec}), {bytes} ETA {eta}",
+        )
+        .expect("valid progress template")
+        .with_key("bytes", move |_: &indicatif::ProgressState, w: &mut dyn std::fmt::Write| {
+            let _ = write!(
+                w,
+                "{}/{}",
+                HumanBytes(scanned.load(Ordering::Relaxed)),
+                HumanBytes(total.load(Ordering::Relaxed))
+            );
+        })
+        .progress_chars("=> ");
+
         Self {
-            total: std::sync::Mutex::new(0),
-            show_progress,
+            bar: ProgressBar::with_draw_target(None, target).with_style(style),
+            bytes,
+            total_bytes,
         }
     }
 }
 
 impl ProgressReporter for TerminalProgressReporter {
-    fn start(&self, total: usize) {
-        if self.show_progress && total > 100 {
-            *self.total.lock().unwrap() = total;
-            print!("{} Scanning files... ", "⏳".cyan());
-            let _ = io::stdout().flush();
+    fn start(&self, total_files: usize, total_bytes: u64) {
+        self.bytes.store(0, Ordering::Relaxed);
+        self.total_bytes.store(total_bytes, Ordering::Relaxed);
+        self.bar.reset();
+        self.bar.set_length(total_files as u64);
+    }
+
+    fn file_scanned(&self, _path: &Path, bytes: u64) {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.bar.inc(1);
+    }
+
+    fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+/// A progress event written by [`JsonProgressReporter`], one JSON object
+/// per line tagged by `event`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    Start {
+        total_files: usize,
+        total_bytes: u64,
+    },
+    Progress {
+        files: usize,
+        total_files: usize,
+        bytes: u64,
+        total_bytes: u64,
+        elapsed_ms: u64,
+    },
+    Finish {
+        files: usize,
+        bytes: u64,
+        elapsed_ms: u64,
+    },
+}
+
+#[derive(Default)]
+struct JsonProgressState {
+    files: usize,
+    total_files: usize,
+    bytes: u64,
+    total_bytes: u64,
+    started: Option<Instant>,
+    last_event: Option<Instant>,
+}
+
+/// Writes [`ProgressEvent`]s as newline-delimited JSON, for tools that wrap
+/// the scanner. Progress events are throttled to one per interval, 100ms by
+/// default.
+pub struct JsonProgressReporter {
+    writer: Mutex<Box<dyn Write + Send>>,
+    interval: Duration,
+    state: Mutex<JsonProgressState>,
+}
+
+impl JsonProgressReporter {
+    /// Write events to stderr.
+    pub fn new() -> Self {
+        Self::with_writer(Box::new(io::stderr()))
+    }
+
+    pub fn with_writer(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+            interval: Duration::from_millis(100),
+            state: Mutex::new(JsonProgressState::default()),
         }
     }
 
-    fn update(&self, current: usize) {
-        if self.show_progress {
-            let total = *self.total.lock().unwrap();
-            if total > 100 && current % 10 == 0 {
-                let percentage = (current as f32 / total as f32 * 100.0) as u32;
-                print!("\r{} Scanning files... {}% ", "⏳".cyan(), percentage);
-                let _ = io::stdout().flush();
-            }
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    fn emit(&self, event: &ProgressEvent) {
+        let mut writer = self.writer.lock().unwrap();
+        // Progress must never fail the scan, e.g. when the reader went away
+        if let Ok(line) = serde_json::to_string(event) {
+            let _ = writeln!(writer, "{}", line);
+            let _ = writer.flush();
         }
     }
+}
+
+impl Default for JsonProgressReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn elapsed_ms(started: Option<Instant>) -> u64 {
+    started.map_or(0, |started| started.elapsed().as_millis() as u64)
+}
+
+impl ProgressReporter for JsonProgressReporter {
+    fn start(&self, total_files: usize, total_bytes: u64) {
+        *self.state.lock().unwrap() = JsonProgressState {
+            total_files,
+            total_bytes,
+            started: Some(Instant::now()),
+            ..Default::default()
+        };
+        self.emit(&ProgressEvent::Start {
+            total_files,
+            total_bytes,
+        });
+    }
+
+    fn file_scanned(&self, _path: &Path, bytes: u64) {
+        let event = {
+            let mut state = self.state.lock().unwrap();
+            state.files += 1;
+            state.bytes += bytes;
+            let now = Instant::now();
+            let due = state
+                .last_event
+                .is_none_or(|last| now.duration_since(last) >= self.interval);
+            if !due && state.files < state.total_files {
+                return;
+            }
+            state.last_event = Some(now);
+            ProgressEvent::Progress {
+                files: state.files,
+                total_files: state.total_files,
+                bytes: state.bytes,
+                total_bytes: state.total_bytes,
+                elapsed_ms: elapsed_ms(state.started),
+            }
+        };
+        self.emit(&event);
+    }
 
     fn finish(&self) {
-        if self.show_progress {
-            let total = *self.total.lock().unwrap();
-            if total > 100 {
-                print!("\r{} Scanning complete!     \n", "✅".green());
-                let _ = io::stdout().flush();
+        let event = {
+            let state = self.state.lock().unwrap();
+            ProgressEvent::Finish {
+                files: state.files,
+                bytes: state.bytes,
+                elapsed_ms: elapsed_ms(state.started),
             }
+        };
+        self.emit(&event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::CodeDebtScanner;
+
+    /// A writer that can be read back after the reporter is moved into the scanner.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
         }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_progress_events() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.rs"), "// TODO: one\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.py"), "# FIXME\n").unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "TODO: skipped\n").unwrap();
+
+        let buffer = SharedBuffer::default();
+        let reporter = JsonProgressReporter::with_writer(Box::new(buffer.clone()))
+            .with_interval(Duration::from_secs(3600));
+        let scanner = CodeDebtScanner::new().with_progress_reporter(Box::new(reporter));
+        assert_eq!(scanner.scan(temp_dir.path()).unwrap().len(), 2);
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let events: Vec<ProgressEvent> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[0],
+            ProgressEvent::Start {
+                total_files: 2,
+                total_bytes: 21
+            }
+        );
+        // The first file is reported at once, the second is throttled
+        // until it completes the scan
+        assert!(matches!(
+            events[2],
+            ProgressEvent::Progress {
+                files: 2,
+                bytes: 21,
+                ..
+            }
+        ));
+        assert!(matches!(
+            events[3],
+            ProgressEvent::Finish {
+                files: 2,
+                bytes: 21,
+                ..
+            }
+        ));
     }
 }
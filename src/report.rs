@@ -0,0 +1,183 @@
+use crate::encoding::Encoding;
+use crate::models::CodeDebtItem;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Version of the [`ScanReport`] JSON layout, bumped on incompatible changes.
+pub const SCAN_REPORT_SCHEMA_VERSION: u32 = 2;
+
+/// Why a file was not scanned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum SkipReason {
+    /// The file could not be read
+    Unreadable { error: String },
+    /// The content has NUL bytes and is not UTF-16
+    Binary,
+    /// Walking the directory failed, e.g. permission denied
+    WalkError { error: String },
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Unreadable { error } => write!(f, "unreadable: {}", error),
+            SkipReason::Binary => write!(f, "binary"),
+            SkipReason::WalkError { error } => write!(f, "walk error: {}", error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub reason: SkipReason,
+}
+
+/// A file that was scanned after decoding it from something other than
+/// plain UTF-8.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscodedFile {
+    pub path: PathBuf,
+    pub encoding: Encoding,
+    /// Invalid sequences were replaced with U+FFFD
+    pub lossy: bool,
+}
+
+/// The items of a scan together with what was scanned, so "no debt" can be
+/// told apart from "nothing scanned".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanReport {
+    /// See [`SCAN_REPORT_SCHEMA_VERSION`]
+    pub schema_version: u32,
+    pub tool_version: String,
+    /// Paths the scan started from
+    pub roots: Vec<PathBuf>,
+    /// Hash of the scanner settings that affect results, see
+    /// [`crate::scanner::CodeDebtScanner::config_hash`]
+    pub config_hash: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub files_scanned: usize,
+    pub bytes_read: u64,
+    pub files_skipped: Vec<SkippedFile>,
+    /// Scanned files that were not plain UTF-8, see [`crate::encoding::decode`]
+    #[serde(default)]
+    pub files_transcoded: Vec<TranscodedFile>,
+    pub items: Vec<CodeDebtItem>,
+}
+
+impl ScanReport {
+    /// An empty report starting now.
+    pub fn new(roots: Vec<PathBuf>, config_hash: impl Into<String>) -> Self {
+        Self {
+            schema_version: SCAN_REPORT_SCHEMA_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            roots,
+            config_hash: config_hash.into(),
+            started_at: Utc::now(),
+            duration_ms: 0,
+            files_scanned: 0,
+            bytes_read: 0,
+            files_skipped: Vec::new(),
+            files_transcoded: Vec::new(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+
+    /// Add the results of another scan run after this one.
+    pub fn merge(&mut self, other: ScanReport) {
+        self.roots.extend(other.roots);
+        self.duration_ms += other.duration_ms;
+        self.files_scanned += other.files_scanned;
+        self.bytes_read += other.bytes_read;
+        self.files_skipped.extend(other.files_skipped);
+        self.files_transcoded.extend(other.files_transcoded);
+        self.items.extend(other.items);
+    }
+
+    /// Drop items reported more than once for the same position of the same
+    /// file, e.g. when overlapping paths were scanned. The first one is kept.
+    pub fn dedup_items(&mut self) {
+        let mut canonical: HashMap<PathBuf, PathBuf> = HashMap::new();
+        let mut seen = HashSet::new();
+        self.items.retain(|item| {
+            let path = canonical
+                .entry(item.file_path.clone())
+                .or_insert_with(|| {
+                    item.file_path
+                        .canonicalize()
+                        .unwrap_or_else(|_| item.file_path.clone())
+                })
+                .clone();
+            seen.insert((
+                path,
+                item.line_number,
+                item.column,
+                item.pattern_type.clone(),
+            ))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_round_trip() {
+        let mut report = ScanReport::new(vec![PathBuf::from("src")], "abc");
+        report.files_skipped.push(SkippedFile {
+            path: PathBuf::from("src/blob.rs"),
+            reason: SkipReason::Binary,
+        });
+        let mut other = ScanReport::new(vec![PathBuf::from("lib")], "abc");
+        other.files_scanned = 2;
+        other.files_skipped.push(SkippedFile {
+            path: PathBuf::from("lib/locked.rs"),
+            reason: SkipReason::Unreadable {
+                error: "permission denied".into(),
+            },
+        });
+        report.merge(other);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["schema_version"], SCAN_REPORT_SCHEMA_VERSION);
+        assert_eq!(json["files_scanned"], 2);
+        assert_eq!(json["files_skipped"][0]["reason"], "binary");
+        assert_eq!(json["files_skipped"][1]["error"], "permission denied");
+
+        let parsed: ScanReport = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.roots.len(), 2);
+        assert_eq!(parsed.files_skipped, report.files_skipped);
+    }
+
+    #[test]
+    fn test_dedup_items_by_canonical_position() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.rs"), "// TODO\n// TODO\n").unwrap();
+        let item = |path: PathBuf, line| {
+            CodeDebtItem::new(path, line, 4, "// TODO", "TODO", crate::Severity::Medium)
+        };
+
+        let mut report = ScanReport::new(Vec::new(), "abc");
+        report.items = vec![
+            item(temp_dir.path().join("a.rs"), 1),
+            item(temp_dir.path().join(".").join("a.rs"), 1),
+            item(temp_dir.path().join("a.rs"), 2),
+        ];
+        report.dedup_items();
+        assert_eq!(report.items.len(), 2);
+        assert_eq!(report.items[0].file_path, temp_dir.path().join("a.rs"));
+        assert_eq!(report.items[1].line_number, 2);
+    }
+}
@@ -0,0 +1,414 @@
+use crate::blame::{AgeFrom, BlameSettings};
+use crate::git::GitAnalyzer;
+use crate::models::{BlameStatus, CodeDebtItem};
+use crate::revision::TreeWalker;
+use crate::scanner::{CodeDebtScanner, FileScan};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use git2::{Commit, DiffOptions, Oid, Repository, Sort, Tree};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A debt item removed by a commit.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedItem {
+    /// The item as it was before the removing commit, with its path
+    /// relative to the repository root and blame telling who introduced it
+    #[serde(flatten)]
+    pub item: CodeDebtItem,
+    pub resolved_commit: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<String>,
+    pub resolved_at: DateTime<Utc>,
+    /// Days from the commit that introduced the item to the one removing it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days_to_resolve: Option<i64>,
+}
+
+/// How many items were resolved and how long they stayed open.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ResolutionStats {
+    pub resolved: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub median_days: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_days: Option<f64>,
+}
+
+impl ResolutionStats {
+    fn from_items<'a>(items: impl Iterator<Item = &'a ResolvedItem>) -> Self {
+        let mut resolved = 0;
+        let mut days = Vec::new();
+        for item in items {
+            resolved += 1;
+            days.extend(item.days_to_resolve);
+        }
+        days.sort_unstable();
+
+        let median_days = match days.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(days[n / 2] as f64),
+            n => Some((days[n / 2 - 1] + days[n / 2]) as f64 / 2.0),
+        };
+        let mean_days =
+            (!days.is_empty()).then(|| days.iter().sum::<i64>() as f64 / days.len() as f64);
+        Self {
+            resolved,
+            median_days,
+            mean_days,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolutionReport {
+    /// The commit the range starts after
+    pub base: String,
+    /// Resolved items, oldest resolution first
+    pub items: Vec<ResolvedItem>,
+    pub by_pattern: BTreeMap<String, ResolutionStats>,
+    /// By the author of the removing commit
+    pub by_author: BTreeMap<String, ResolutionStats>,
+}
+
+/// Find debt removed by the commits reachable from `rev` but not from
+/// `since`, only counting files under `prefix` if given. Items that are
+/// removed and added again with the same surrounding lines in the same
+/// commit, e.g. moved or reindented, are not resolved. Merge commits are
+/// skipped because the commits they bring in are walked themselves.
+pub fn resolved_since(
+    scanner: &CodeDebtScanner,
+    repo: &Repository,
+    since: &str,
+    rev: &str,
+    prefix: Option<&Path>,
+) -> Result<ResolutionReport> {
+    let peel = |spec: &str| {
+        repo.revparse_single(spec)
+            .and_then(|object| object.peel_to_commit())
+            .with_context(|| format!("Unknown git revision '{}'", spec))
+    };
+    let base = peel(since)?;
+    let end = peel(rev)?;
+
+    let mut walk = repo.revwalk()?;
+    walk.push(end.id())?;
+    walk.hide(base.id())?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+
+    let walker = TreeWalker::new(scanner)?;
+    let settings = BlameSettings::resolve(repo, &scanner.blame_config);
+    let mut items = Vec::new();
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_count() != 1 {
+            continue;
+        }
+        items.extend(resolved_by(
+            scanner, repo, &walker, &settings, &commit, prefix,
+        )?);
+    }
+
+    let mut by_pattern: BTreeMap<String, Vec<&ResolvedItem>> = BTreeMap::new();
+    let mut by_author: BTreeMap<String, Vec<&ResolvedItem>> = BTreeMap::new();
+    for item in &items {
+        by_pattern
+            .entry(item.item.pattern_type.clone())
+            .or_default()
+            .push(item);
+        let author = item.resolved_by.as_deref().unwrap_or("unknown");
+        by_author.entry(author.to_string()).or_default().push(item);
+    }
+    let stats = |groups: BTreeMap<String, Vec<&ResolvedItem>>| {
+        groups
+            .into_iter()
+            .map(|(key, items)| (key, ResolutionStats::from_items(items.into_iter())))
+            .collect()
+    };
+
+    Ok(ResolutionReport {
+        base: base.id().to_string(),
+        by_pattern: stats(by_pattern),
+        by_author: stats(by_author),
+        items,
+    })
+}
+
+/// Debt items that `commit` removed from its parent.
+fn resolved_by(
+    scanner: &CodeDebtScanner,
+    repo: &Repository,
+    walker: &TreeWalker,
+    settings: &BlameSettings,
+    commit: &Commit,
+    prefix: Option<&Path>,
+) -> Result<Vec<ResolvedItem>> {
+    let parent = commit.parent(0)?;
+    let parent_tree = parent.tree()?;
+    let mut options = DiffOptions::new();
+    options.context_lines(0);
+    let mut diff = repo.diff_tree_to_tree(
+        Some(&parent_tree),
+        Some(&commit.tree()?),
+        Some(&mut options),
+    )?;
+    // Renamed files are not resolved, only their changed lines are
+    diff.find_similar(None)?;
+
+    let mut removed: HashMap<PathBuf, HashSet<usize>> = HashMap::new();
+    let mut added: HashMap<PathBuf, HashSet<usize>> = HashMap::new();
+    diff.foreach(
+        &mut |_, _| true,
+        None,
+        None,
+        Some(&mut |delta, _, line| {
+            let (lines, path, lineno) = match line.origin() {
+                '-' => (&mut removed, delta.old_file().path(), line.old_lineno()),
+                '+' => (&mut added, delta.new_file().path(), line.new_lineno()),
+                _ => return true,
+            };
+            if let (Some(path), Some(lineno)) = (path, lineno) {
+                lines
+                    .entry(path.to_path_buf())
+                    .or_default()
+                    .insert(lineno as usize);
+            }
+            true
+        }),
+    )?;
+    if removed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut removed_items = debt_on_lines(scanner, repo, walker, &parent_tree, prefix, &removed)?;
+    if removed_items.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Each item added back counts as moved for one removed item with the
+    // same fingerprint, i.e. pattern, text and surrounding lines, so a
+    // generic TODO written elsewhere does not hide another being resolved
+    let mut moved: HashMap<String, usize> = HashMap::new();
+    for (_, _, items) in debt_on_lines(scanner, repo, walker, &commit.tree()?, None, &added)? {
+        for item in items {
+            *moved.entry(item.fingerprint).or_default() += 1;
+        }
+    }
+    for (_, _, items) in &mut removed_items {
+        items.retain(|item| match moved.get_mut(&item.fingerprint) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        });
+    }
+
+    let age_from = scanner.blame_config.age_from;
+    let resolved_at = DateTime::from_timestamp(
+        match age_from {
+            AgeFrom::Commit => commit.time().seconds(),
+            AgeFrom::Author => commit.author().when().seconds(),
+        },
+        0,
+    )
+    .unwrap_or_default();
+    let resolved_by = commit.author().name().map(|name| name.to_string());
+
+    let mut resolved = Vec::new();
+    for (path, blob, gone) in removed_items {
+        if gone.is_empty() {
+            continue;
+        }
+
+        let blame = GitAnalyzer::blame_file(repo, parent.id(), &path, blob, settings);
+        for mut item in gone {
+            if let Some(hunk) = blame
+                .as_ref()
+                .and_then(|blame| blame.line(item.line_number))
+            {
+                item.blame_status = Some(BlameStatus::Committed);
+                item.author = hunk.author.clone();
+                item.commit_hash = Some(hunk.commit.clone());
+                item.created_at = DateTime::from_timestamp(hunk.time(age_from), 0);
+            }
+            let days_to_resolve = item
+                .created_at
+                .map(|created| resolved_at.signed_duration_since(created).num_days().max(0));
+            resolved.push(ResolvedItem {
+                item,
+                resolved_commit: commit.id().to_string(),
+                resolved_by: resolved_by.clone(),
+                resolved_at,
+                days_to_resolve,
+            });
+        }
+    }
+    resolved.sort_by(|a, b| {
+        a.item
+            .file_path
+            .cmp(&b.item.file_path)
+            .then_with(|| a.item.line_number.cmp(&b.item.line_number))
+    });
+    Ok(resolved)
+}
+
+/// The debt items on the given lines of the scanned files of `tree`, by
+/// file in path order.
+fn debt_on_lines(
+    scanner: &CodeDebtScanner,
+    repo: &Repository,
+    walker: &TreeWalker,
+    tree: &Tree,
+    prefix: Option<&Path>,
+    lines: &HashMap<PathBuf, HashSet<usize>>,
+) -> Result<Vec<(PathBuf, Oid, Vec<CodeDebtItem>)>> {
+    let mut files: Vec<_> = walker
+        .files(repo, tree, prefix)?
+        .into_iter()
+        .filter(|file| lines.contains_key(&file.path))
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut found = Vec::new();
+    for file in files {
+        let blob = repo.find_blob(file.blob)?;
+        let FileScan::Scanned { items, .. } = CodeDebtScanner::scan_bytes(
+            &file.path,
+            blob.content(),
+            &scanner.matcher,
+            &scanner.options,
+        ) else {
+            continue;
+        };
+        let on_lines = &lines[&file.path];
+        let items: Vec<CodeDebtItem> = items
+            .into_iter()
+            .filter(|item| on_lines.contains(&item.line_number))
+            .collect();
+        if !items.is_empty() {
+            found.push((file.path, file.blob, items));
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::commit;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_resolved_since() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let base = commit(&repo, &[("a.rs", Some("fn a() {}\n"))], "Ann", 0);
+        commit(
+            &repo,
+            &[("a.rs", Some("// TODO: one\n// HACK: two\nfn a() {}\n"))],
+            "Ann",
+            DAY,
+        );
+        commit(
+            &repo,
+            &[("a.rs", Some("// HACK: two\nfn a() {}\n"))],
+            "Bob",
+            11 * DAY,
+        );
+        // Moving the HACK to another file does not resolve it
+        commit(
+            &repo,
+            &[
+                ("a.rs", Some("fn main() {}\n")),
+                ("b.rs", Some("// HACK: two\nfn a() {}\n")),
+            ],
+            "Cat",
+            21 * DAY,
+        );
+        commit(&repo, &[("b.rs", None)], "Cat", 25 * DAY);
+
+        let scanner = CodeDebtScanner::new();
+        let report = resolved_since(&scanner, &repo, &base.to_string(), "HEAD", None).unwrap();
+
+        let resolved: Vec<_> = report
+            .items
+            .iter()
+            .map(|item| {
+                (
+                    item.item.pattern_type.as_str(),
+                    item.resolved_by.as_deref().unwrap(),
+                    item.days_to_resolve.unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(resolved, vec![("TODO", "Bob", 10), ("HACK", "Cat", 4)]);
+        assert_eq!(report.items[0].item.author.as_deref(), Some("Ann"));
+        assert_eq!(report.by_pattern["TODO"].resolved, 1);
+        assert_eq!(report.by_author["Cat"].median_days, Some(4.0));
+    }
+
+    #[test]
+    fn test_generic_line_added_elsewhere_is_not_a_move() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let base = commit(
+            &repo,
+            &[
+                ("a.rs", Some("fn a() {}\n// TODO\n")),
+                ("b.rs", Some("fn b() {}\n")),
+            ],
+            "Ann",
+            0,
+        );
+        commit(
+            &repo,
+            &[
+                ("a.rs", Some("fn a() {}\n")),
+                ("b.rs", Some("fn b() {}\n// TODO\n")),
+            ],
+            "Bob",
+            3 * DAY,
+        );
+
+        let scanner = CodeDebtScanner::new();
+        let report = resolved_since(&scanner, &repo, &base.to_string(), "HEAD", None).unwrap();
+        assert_eq!(report.items.len(), 1);
+        assert_eq!(report.items[0].item.file_path, PathBuf::from("a.rs"));
+    }
+
+    #[test]
+    fn test_stats() {
+        let item = |days| ResolvedItem {
+            item: CodeDebtItem::new(
+                PathBuf::from("a.rs"),
+                1,
+                1,
+                "// TODO",
+                "TODO",
+                crate::models::Severity::Medium,
+            ),
+            resolved_commit: String::new(),
+            resolved_by: None,
+            resolved_at: DateTime::default(),
+            days_to_resolve: days,
+        };
+        let items = [
+            item(Some(1)),
+            item(Some(5)),
+            item(None),
+            item(Some(2)),
+            item(Some(10)),
+        ];
+        assert_eq!(
+            ResolutionStats::from_items(items.iter()),
+            ResolutionStats {
+                resolved: 5,
+                median_days: Some(3.5),
+                mean_days: Some(4.5),
+            }
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use crate::scanner::{CodeDebtScanner, IGNORE_FILE_NAME};
+use anyhow::{anyhow, Context, Result};
+use git2::{FileMode, ObjectType, Oid, Repository, Tree, TreeWalkMode, TreeWalkResult};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::{Override, OverrideBuilder};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A file in a commit's tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeFile {
+    /// Path relative to the repository root
+    pub path: PathBuf,
+    pub blob: Oid,
+}
+
+/// Open the repository containing `path`. If `path` is a subdirectory of
+/// the working directory, it is returned relative to the repository root.
+pub fn open_repository(path: &Path) -> Result<(Repository, Option<PathBuf>)> {
+    let repo = Repository::discover(path)
+        .with_context(|| format!("{} is not in a git repository", path.display()))?;
+    let prefix = match repo.workdir() {
+        Some(workdir) => path
+            .canonicalize()?
+            .strip_prefix(workdir.canonicalize()?)
+            .ok()
+            .filter(|prefix| !prefix.as_os_str().is_empty())
+            .map(Path::to_path_buf),
+        None => None,
+    };
+    Ok((repo, prefix))
+}
+
+/// Run `scan` on the raw content of each file in parallel.
+pub(crate) fn scan_blobs<T, F>(repo: &Repository, files: &[TreeFile], scan: F) -> Result<Vec<T>>
+where
+    T: Send,
+    F: Fn(&TreeFile, &[u8]) -> T + Sync,
+{
+    let git_dir = repo.path();
+    files
+        .par_iter()
+        .map_init(
+            // Repository handles can't be shared between threads
+            || Repository::open(git_dir),
+            |repo, file| {
+                let repo = repo.as_ref().map_err(|e| anyhow!("{}", e))?;
+                let blob = repo.find_blob(file.blob)?;
+                Ok(scan(file, blob.content()))
+            },
+        )
+        .collect()
+}
+
+/// Lists the files of commit trees straight from the object database,
+/// applying the same filters as a working-directory scan: extensions,
+/// ignored directory names, include/exclude globs and `.codedebtignore`
+/// files committed in the tree.
+pub struct TreeWalker {
+    extensions: HashSet<String>,
+    ignore_dirs: HashSet<String>,
+    overrides: Override,
+}
+
+impl TreeWalker {
+    pub fn new(scanner: &CodeDebtScanner) -> Result<Self> {
+        let mut overrides = OverrideBuilder::new("");
+        for glob in &scanner.include_globs {
+            overrides.add(glob)?;
+        }
+        for glob in &scanner.exclude_globs {
+            overrides.add(&format!("!{}", glob))?;
+        }
+
+        Ok(Self {
+            extensions: scanner.file_extensions.iter().cloned().collect(),
+            ignore_dirs: scanner.ignore_dirs.iter().cloned().collect(),
+            overrides: overrides.build()?,
+        })
+    }
+
+    /// The files of `tree` that would be scanned, under `prefix` if given.
+    pub fn files(
+        &self,
+        repo: &Repository,
+        tree: &Tree,
+        prefix: Option<&Path>,
+    ) -> Result<Vec<TreeFile>> {
+        let mut blobs = Vec::new();
+        let mut ignore_files = Vec::new();
+        tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+            let Some(name) = entry.name() else {
+                return TreeWalkResult::Skip;
+            };
+            if entry.kind() == Some(ObjectType::Tree) {
+                return if self.ignore_dirs.contains(name) {
+                    TreeWalkResult::Skip
+                } else {
+                    TreeWalkResult::Ok
+                };
+            }
+            // Symlinks and submodules have no content of their own
+            let mode = entry.filemode_raw();
+            if mode != i32::from(FileMode::Blob) && mode != i32::from(FileMode::BlobExecutable) {
+                return TreeWalkResult::Ok;
+            }
+            let path = Path::new(dir).join(name);
+            if name == IGNORE_FILE_NAME {
+                ignore_files.push((PathBuf::from(dir), entry.id()));
+            }
+            blobs.push((path, entry.id()));
+            TreeWalkResult::Ok
+        })
+        .context("Failed to walk git tree")?;
+
+        let ignores = ignore_files
+            .into_iter()
+            .map(|(dir, blob)| Ok((dir.clone(), Self::load_ignore(repo, &dir, blob)?)))
+            .collect::<Result<HashMap<PathBuf, Gitignore>>>()?;
+
+        Ok(blobs
+            .into_iter()
+            .filter(|(path, _)| prefix.is_none_or(|prefix| path.starts_with(prefix)))
+            .filter(|(path, _)| self.is_scanned(path, &ignores))
+            .map(|(path, blob)| TreeFile { path, blob })
+            .collect())
+    }
+
+    fn load_ignore(repo: &Repository, dir: &Path, blob: Oid) -> Result<Gitignore> {
+        let blob = repo.find_blob(blob)?;
+        let mut builder = GitignoreBuilder::new(dir);
+        for line in String::from_utf8_lossy(blob.content()).lines() {
+            builder.add_line(None, line)?;
+        }
+        Ok(builder.build()?)
+    }
+
+    fn is_scanned(&self, path: &Path, ignores: &HashMap<PathBuf, Gitignore>) -> bool {
+        let has_extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| self.extensions.contains(ext));
+        if !has_extension {
+            return false;
+        }
+
+        // Directories are matched first, like a walk that skips them
+        let dirs: Vec<&Path> = path
+            .ancestors()
+            .skip(1)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .collect();
+        if dirs
+            .iter()
+            .rev()
+            .any(|dir| self.overrides.matched(dir, true).is_ignore())
+            || self.overrides.matched(path, false).is_ignore()
+        {
+            return false;
+        }
+
+        !ignores.iter().any(|(dir, ignore)| {
+            path.starts_with(dir) && ignore.matched_path_or_any_parents(path, false).is_ignore()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{commit, now};
+
+    #[test]
+    fn test_tree_files_are_filtered() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let head = commit(
+            &repo,
+            &[
+                ("src/lib.rs", Some("// TODO")),
+                ("src/gen/api.rs", Some("// TODO")),
+                ("src/lib_test.rs", Some("// TODO")),
+                ("node_modules/dep/index.js", Some("// TODO")),
+                ("docs/notes.txt", Some("TODO")),
+                ("tools/build.py", Some("# TODO")),
+                ("src/.codedebtignore", Some("gen/\n")),
+            ],
+            "Dev",
+            now(),
+        );
+        let tree = repo.find_commit(head).unwrap().tree().unwrap();
+
+        let paths = |scanner: CodeDebtScanner, prefix: Option<&Path>| {
+            let walker = TreeWalker::new(&scanner).unwrap();
+            let mut paths: Vec<String> = walker
+                .files(&repo, &tree, prefix)
+                .unwrap()
+                .into_iter()
+                .map(|file| file.path.to_string_lossy().into_owned())
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(
+            paths(CodeDebtScanner::new(), None),
+            vec!["src/lib.rs", "src/lib_test.rs", "tools/build.py"]
+        );
+        assert_eq!(
+            paths(
+                CodeDebtScanner::new().with_exclude(vec!["*_test.rs".into()]),
+                Some(Path::new("src"))
+            ),
+            vec!["src/lib.rs"]
+        );
+    }
+
+    #[test]
+    fn test_scan_revision() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit(
+            &repo,
+            &[
+                ("src/lib.rs", Some("// TODO: committed\n")),
+                ("main.rs", Some("// HACK\n")),
+            ],
+            "Dev",
+            now(),
+        );
+        std::fs::write(temp_dir.path().join("src/lib.rs"), "// FIXME: local\n").unwrap();
+
+        let scanner = CodeDebtScanner::new();
+        let items = scanner.scan_revision(temp_dir.path(), "HEAD").unwrap();
+        assert_eq!(items.len(), 2);
+
+        let src = temp_dir.path().join("src");
+        let items = scanner.scan_revision(&src, "HEAD").unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].pattern_type, "TODO");
+        assert_eq!(items[0].file_path, src.join("lib.rs"));
+
+        assert!(scanner.scan_revision(temp_dir.path(), "missing").is_err());
+    }
+}
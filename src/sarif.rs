@@ -0,0 +1,351 @@
+use crate::models::{CodeDebtItem, Severity};
+use crate::patterns::Pattern;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::{Component, Path};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+/// Characters escaped in a relative URI path segment (RFC 3986)
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+/// A SARIF 2.1.0 log with a single run.
+#[derive(Debug, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<Run>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Run {
+    pub tool: Tool,
+    /// Columns count characters, not the default UTF-16 code units
+    pub column_kind: &'static str,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Tool {
+    pub driver: Driver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Driver {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub information_uri: &'static str,
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    pub id: String,
+    pub name: String,
+    pub short_description: Message,
+    pub default_configuration: RuleConfiguration,
+    pub properties: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuleConfiguration {
+    pub level: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_index: Option<usize>,
+    pub level: &'static str,
+    pub message: Message,
+    pub locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<Suppression>,
+    /// Lets code scanning services track results across line shifts
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub partial_fingerprints: Map<String, Value>,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub properties: Map<String, Value>,
+}
+
+/// Items silenced by a `codedebt:ignore` directive are suppressed in source.
+#[derive(Debug, Serialize)]
+pub struct Suppression {
+    pub kind: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Message {
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub physical_location: PhysicalLocation,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalLocation {
+    pub artifact_location: ArtifactLocation,
+    pub region: Region,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArtifactLocation {
+    pub uri: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    pub start_line: usize,
+    pub start_column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
+    pub snippet: Message,
+}
+
+/// SARIF level for a severity.
+pub fn level(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low => "note",
+    }
+}
+
+/// Build a SARIF log with one rule per pattern and one result per item.
+pub fn to_sarif(items: &[CodeDebtItem], patterns: &[Pattern]) -> SarifLog {
+    let rules: Vec<Rule> = patterns
+        .iter()
+        .map(|pattern| {
+            let mut properties = Map::new();
+            properties.insert("severity".into(), format!("{:?}", pattern.severity).into());
+            properties.insert("regex".into(), pattern.regex.as_str().into());
+            Rule {
+                id: pattern.name.clone(),
+                name: pattern.name.clone(),
+                short_description: Message {
+                    text: format!("{} code debt marker", pattern.name),
+                },
+                default_configuration: RuleConfiguration {
+                    level: level(&pattern.severity),
+                },
+                properties,
+            }
+        })
+        .collect();
+
+    let results = items
+        .iter()
+        .map(|item| SarifResult {
+            rule_id: item.pattern_type.clone(),
+            rule_index: rules.iter().position(|rule| rule.id == item.pattern_type),
+            level: level(&item.severity),
+            message: Message {
+                text: item
+                    .message
+                    .clone()
+                    .unwrap_or_else(|| item.line_content.clone()),
+            },
+            locations: vec![Location {
+                physical_location: PhysicalLocation {
+                    artifact_location: ArtifactLocation {
+                        uri: artifact_uri(&item.file_path),
+                    },
+                    region: Region {
+                        start_line: item.line_number,
+                        start_column: item.column,
+                        end_column: (item.end_column > item.column).then_some(item.end_column),
+                        snippet: Message {
+                            text: item.line_content.clone(),
+                        },
+                    },
+                },
+            }],
+            suppressions: if item.suppressed {
+                vec![Suppression { kind: "inSource" }]
+            } else {
+                Vec::new()
+            },
+            partial_fingerprints: if item.fingerprint.is_empty() {
+                Map::new()
+            } else {
+                Map::from_iter([("codedebt/v1".to_string(), item.fingerprint.clone().into())])
+            },
+            properties: result_properties(item),
+        })
+        .collect();
+
+    SarifLog {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![Run {
+            tool: Tool {
+                driver: Driver {
+                    name: env!("CARGO_PKG_NAME"),
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: env!("CARGO_PKG_HOMEPAGE"),
+                    rules,
+                },
+            },
+            column_kind: "unicodeCodePoints",
+            results,
+        }],
+    }
+}
+
+/// Relative paths use forward slashes without a leading `./`, absolute
+/// paths become `file://` URIs. Segments are percent-encoded.
+fn artifact_uri(path: &Path) -> String {
+    if path.is_absolute() {
+        if let Ok(url) = url::Url::from_file_path(path) {
+            return url.to_string();
+        }
+    }
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| {
+            utf8_percent_encode(&component.as_os_str().to_string_lossy(), PATH_SEGMENT).to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn result_properties(item: &CodeDebtItem) -> Map<String, Value> {
+    let mut properties = Map::new();
+    if let Some(status) = item.blame_status {
+        properties.insert("blameStatus".into(), status.as_str().into());
+    }
+    if let Some(author) = &item.author {
+        properties.insert("author".into(), author.clone().into());
+    }
+    if let Some(commit_hash) = &item.commit_hash {
+        properties.insert("commitHash".into(), commit_hash.clone().into());
+    }
+    if let Some(created_at) = item.created_at {
+        properties.insert("createdAt".into(), created_at.to_rfc3339().into());
+    }
+    if let Some(age_days) = item.age_days {
+        properties.insert("ageDays".into(), age_days.into());
+    }
+    if let Some(assignee) = &item.assignee {
+        properties.insert("assignee".into(), assignee.clone().into());
+    }
+    if !item.issue_refs.is_empty() {
+        properties.insert("issueRefs".into(), item.issue_refs.clone().into());
+    }
+    if let Some(due_date) = item.due_date {
+        properties.insert("dueDate".into(), due_date.to_string().into());
+    }
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_sarif_rules_and_results() {
+        let mut item = CodeDebtItem::new(
+            PathBuf::from("./src/lib.rs"),
+            12,
+            4,
+            "// FIXME: broken",
+            "FIXME",
+            Severity::High,
+        );
+        item.author = Some("alice".to_string());
+        item.end_column = 9;
+
+        let log = to_sarif(&[item], &Pattern::default_patterns());
+        let json = serde_json::to_value(&log).unwrap();
+
+        assert_eq!(json["version"], "2.1.0");
+        let run = &json["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), Pattern::default_patterns().len());
+
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "FIXME");
+        assert_eq!(
+            rules[result["ruleIndex"].as_u64().unwrap() as usize]["id"],
+            "FIXME"
+        );
+        assert_eq!(result["level"], "error");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/lib.rs");
+        assert_eq!(location["region"]["startLine"], 12);
+        assert_eq!(location["region"]["endColumn"], 9);
+        assert_eq!(run["columnKind"], "unicodeCodePoints");
+        assert_eq!(result["properties"]["author"], "alice");
+        assert!(result.get("suppressions").is_none());
+    }
+
+    #[test]
+    fn test_suppressed_results() {
+        let mut item = CodeDebtItem::new(
+            PathBuf::from("src/lib.rs"),
+            1,
+            1,
+            "// HACK: documented codedebt:ignore",
+            "HACK",
+            Severity::High,
+        );
+        item.suppressed = true;
+
+        let json = serde_json::to_value(to_sarif(&[item], &[])).unwrap();
+        assert_eq!(
+            json["runs"][0]["results"][0]["suppressions"][0]["kind"],
+            "inSource"
+        );
+    }
+
+    #[test]
+    fn test_artifact_uri_encoding() {
+        assert_eq!(
+            artifact_uri(Path::new("./my dir/a#1%.rs")),
+            "my%20dir/a%231%25.rs"
+        );
+        assert_eq!(artifact_uri(Path::new("src/lib.rs")), "src/lib.rs");
+        #[cfg(unix)]
+        assert_eq!(
+            artifact_uri(Path::new("/home/me/my project/lib.rs")),
+            "file:///home/me/my%20project/lib.rs"
+        );
+    }
+
+    #[test]
+    fn test_severity_levels() {
+        assert_eq!(level(&Severity::Critical), "error");
+        assert_eq!(level(&Severity::Medium), "warning");
+        assert_eq!(level(&Severity::Low), "note");
+    }
+}
@@ -1,21 +1,61 @@
+use crate::annotation::Annotation;
+use crate::blame::BlameConfig;
+use crate::encoding::{self, Decoded, Encoding};
+use crate::fingerprint;
 use crate::git::GitAnalyzer;
-use crate::models::{CodeDebtItem, Severity};
+use crate::lexer::{self, Language};
+use crate::matcher::PatternMatcher;
+use crate::models::{BlameStatus, CodeDebtItem, Hotspot, Severity};
 use crate::patterns::Pattern;
 use crate::progress::ProgressReporter;
-use anyhow::Result;
-use git2::Repository;
+use crate::report::{ScanReport, SkipReason, SkippedFile, TranscodedFile};
+use crate::revision::{self, TreeWalker};
+use crate::suppression::{self, Suppressions};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use ignore::overrides::OverrideBuilder;
 use ignore::WalkBuilder;
-use std::collections::{HashMap, HashSet};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::time::Instant;
+
+/// Per-directory ignore file using gitignore syntax, honored even outside git.
+pub const IGNORE_FILE_NAME: &str = ".codedebtignore";
+
+/// Options controlling how file content is matched against patterns.
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Only report matches inside comments for languages with known syntax
+    pub comments_only: bool,
+    /// Keep items silenced by suppression directives, marked as suppressed
+    pub include_suppressed: bool,
+    /// Report every match of a pattern on a line instead of only the first
+    pub all_matches: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            comments_only: true,
+            include_suppressed: false,
+            all_matches: false,
+        }
+    }
+}
 
 pub struct CodeDebtScanner {
-    pub(crate) patterns: Vec<Pattern>,
+    pub(crate) matcher: PatternMatcher,
+    pub(crate) options: ScanOptions,
     pub(crate) file_extensions: Vec<String>,
     pub(crate) ignore_dirs: Vec<String>,
+    pub(crate) include_globs: Vec<String>,
+    pub(crate) exclude_globs: Vec<String>,
     pub(crate) enable_git_blame: bool,
+    pub(crate) blame_config: BlameConfig,
+    pub(crate) reference_time: Option<DateTime<Utc>>,
     pub(crate) detect_duplicates: bool,
-    pub(crate) git_repo: Option<Repository>,
     pub(crate) progress_reporter: Option<Box<dyn ProgressReporter>>,
 }
 
@@ -28,12 +68,16 @@ impl Default for CodeDebtScanner {
 impl CodeDebtScanner {
     pub fn new() -> Self {
         Self {
-            patterns: Pattern::default_patterns(),
+            matcher: PatternMatcher::new(Pattern::default_patterns()),
+            options: ScanOptions::default(),
             file_extensions: Pattern::default_file_extensions(),
             ignore_dirs: Pattern::default_ignore_dirs(),
+            include_globs: Vec::new(),
+            exclude_globs: Vec::new(),
             enable_git_blame: false,
+            blame_config: BlameConfig::default(),
+            reference_time: None,
             detect_duplicates: false,
-            git_repo: None,
             progress_reporter: None,
         }
     }
@@ -44,7 +88,14 @@ impl CodeDebtScanner {
             // Keep default patterns if no patterns provided
             return self;
         }
-        self.patterns = patterns;
+        self.matcher = PatternMatcher::new(patterns);
+        self
+    }
+
+    /// Use an already built matcher, e.g. [`PatternMatcher::naive`] to
+    /// compare against.
+    pub fn with_matcher(mut self, matcher: PatternMatcher) -> Self {
+        self.matcher = matcher;
         self
     }
 
@@ -58,14 +109,50 @@ impl CodeDebtScanner {
         self
     }
 
+    /// Only scan files matching one of these globs (gitignore syntax,
+    /// relative to the scan root).
+    pub fn with_include(mut self, globs: Vec<String>) -> Self {
+        self.include_globs = globs;
+        self
+    }
+
+    /// Skip files and directories matching any of these globs (gitignore
+    /// syntax, relative to the scan root).
+    pub fn with_exclude(mut self, globs: Vec<String>) -> Self {
+        self.exclude_globs = globs;
+        self
+    }
+
+    pub fn with_comments_only(mut self, enable: bool) -> Self {
+        self.options.comments_only = enable;
+        self
+    }
+
+    pub fn with_suppressed(mut self, include: bool) -> Self {
+        self.options.include_suppressed = include;
+        self
+    }
+
+    pub fn with_all_matches(mut self, enable: bool) -> Self {
+        self.options.all_matches = enable;
+        self
+    }
+
+    /// Blame items against the repository owning each file.
     pub fn with_git_blame(mut self, enable: bool) -> Self {
         self.enable_git_blame = enable;
-        if enable {
-            // Try to open git repository
-            if let Ok(repo) = Repository::discover(".") {
-                self.git_repo = Some(repo);
-            }
-        }
+        self
+    }
+
+    pub fn with_blame_config(mut self, config: BlameConfig) -> Self {
+        self.blame_config = config;
+        self
+    }
+
+    /// Measure ages up to this time instead of the time of the scan, so
+    /// reports can be reproduced later.
+    pub fn with_reference_time(mut self, now: DateTime<Utc>) -> Self {
+        self.reference_time = Some(now);
         self
     }
 
@@ -79,113 +166,350 @@ impl CodeDebtScanner {
         self
     }
 
-    pub fn scan<P: AsRef<Path>>(&self, root_path: P) -> Result<Vec<CodeDebtItem>> {
-        let patterns = Arc::new(&self.patterns);
-        let extensions: HashSet<String> = self.file_extensions.iter().cloned().collect();
+    pub fn patterns(&self) -> &[Pattern] {
+        self.matcher.patterns()
+    }
 
-        // Count total files for progress reporting
-        let total_files = if self.progress_reporter.is_some() {
-            self.count_files(&root_path)?
-        } else {
-            0
-        };
+    pub fn ignore_dirs(&self) -> &[String] {
+        &self.ignore_dirs
+    }
+
+    pub fn blame_config(&self) -> &BlameConfig {
+        &self.blame_config
+    }
 
-        if let Some(reporter) = &self.progress_reporter {
-            reporter.start(total_files);
+    /// A walker over `root_path` honoring gitignore rules, `.codedebtignore`
+    /// files, the ignored directory names and the include/exclude globs.
+    fn walker<P: AsRef<Path>>(&self, root_path: P) -> Result<WalkBuilder> {
+        let root_path = root_path.as_ref();
+        let mut overrides = OverrideBuilder::new(root_path);
+        for glob in &self.include_globs {
+            overrides.add(glob)?;
+        }
+        // Added last so that excludes win over includes
+        for glob in &self.exclude_globs {
+            overrides.add(&format!("!{}", glob))?;
         }
 
-        let walker = WalkBuilder::new(&root_path)
+        let ignore_dirs: HashSet<String> = self.ignore_dirs.iter().cloned().collect();
+        let mut builder = WalkBuilder::new(root_path);
+        builder
             .hidden(false)
             .ignore(true)
             .git_ignore(true)
-            .build_parallel();
+            .add_custom_ignore_filename(IGNORE_FILE_NAME)
+            .overrides(overrides.build()?)
+            .filter_entry(move |entry| {
+                // Never skip the root itself, even if it is e.g. `build/`
+                entry.depth() == 0
+                    || !entry.file_type().is_some_and(|t| t.is_dir())
+                    || !entry
+                        .file_name()
+                        .to_str()
+                        .is_some_and(|name| ignore_dirs.contains(name))
+            });
+        Ok(builder)
+    }
 
-        let (tx, rx) = std::sync::mpsc::channel();
-        let progress_tx = tx.clone();
+    pub fn scan<P: AsRef<Path>>(&self, root_path: P) -> Result<Vec<CodeDebtItem>> {
+        Ok(self.scan_report(root_path)?.items)
+    }
 
+    /// Scan a directory, reporting what was scanned and skipped along with
+    /// the items.
+    pub fn scan_report<P: AsRef<Path>>(&self, root_path: P) -> Result<ScanReport> {
+        let root_path = root_path.as_ref();
+        let started = Instant::now();
+        let mut report = ScanReport::new(vec![root_path.to_path_buf()], self.config_hash());
+        let files = self.walk_files(root_path, &mut report.files_skipped)?;
+
+        self.scan_paths(&files, &mut report);
+        self.finish_items(&mut report.items);
+        report.duration_ms = started.elapsed().as_millis() as u64;
+        Ok(report)
+    }
+
+    /// The files a scan of `root_path` would read.
+    pub fn walked_files<P: AsRef<Path>>(&self, root_path: P) -> Result<Vec<PathBuf>> {
+        let files = self.walk_files(root_path.as_ref(), &mut Vec::new())?;
+        Ok(files.into_iter().map(|(path, _)| path).collect())
+    }
+
+    /// Walk `root_path` for the files to scan with their sizes, adding the
+    /// entries that could not be read to `skipped`.
+    fn walk_files(
+        &self,
+        root_path: &Path,
+        skipped: &mut Vec<SkippedFile>,
+    ) -> Result<Vec<(PathBuf, u64)>> {
+        let extensions: HashSet<String> = self.file_extensions.iter().cloned().collect();
+        let walker = self.walker(root_path)?.build_parallel();
+
+        // Collect the files in a single walk, so progress knows the totals
+        // without walking twice
+        let (tx, rx) = std::sync::mpsc::channel();
         walker.run(|| {
             let tx = tx.clone();
-            let progress_tx = progress_tx.clone();
-            let patterns = Arc::clone(&patterns);
-            let extensions = extensions.clone();
-
+            let extensions = &extensions;
             Box::new(move |entry| {
-                match entry {
-                    Ok(entry) => {
-                        let path = entry.path();
-
-                        if path.is_file() {
-                            if let Some(ext) = path.extension() {
-                                if let Some(ext_str) = ext.to_str() {
-                                    if extensions.contains(ext_str) {
-                                        if let Ok(content) = std::fs::read_to_string(path) {
-                                            let items =
-                                                Self::scan_content(path, &content, &patterns);
-                                            for item in items {
-                                                let _ = tx.send(item);
-                                            }
-                                        }
-                                        // Send progress update
-                                        let _ = progress_tx.send(CodeDebtItem {
-                                            file_path: PathBuf::new(),
-                                            line_number: 0,
-                                            column: 0,
-                                            line_content: String::new(),
-                                            pattern_type: "__PROGRESS__".to_string(),
-                                            severity: Severity::Low,
-                                            author: None,
-                                            age_days: None,
-                                            commit_hash: None,
-                                            created_at: None,
-                                            file_extension: None,
-                                            duplicate_count: 0,
-                                        });
-                                    }
-                                }
-                            }
-                        }
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        let path = walk_error_path(&e).unwrap_or(root_path).to_path_buf();
+                        let reason = SkipReason::WalkError {
+                            error: e.to_string(),
+                        };
+                        let _ = tx.send(Err(SkippedFile { path, reason }));
+                        return ignore::WalkState::Continue;
                     }
-                    Err(_) => {
-                        // Skip files we can't access (permissions, etc)
+                };
+                let path = entry.path();
+                let scanned = path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| extensions.contains(ext));
+                if scanned {
+                    match std::fs::metadata(path) {
+                        Ok(metadata) if metadata.is_file() => {
+                            let _ = tx.send(Ok((path.to_path_buf(), metadata.len())));
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            let _ = tx.send(Err(SkippedFile {
+                                path: path.to_path_buf(),
+                                reason: SkipReason::Unreadable {
+                                    error: e.to_string(),
+                                },
+                            }));
+                        }
                     }
                 }
                 ignore::WalkState::Continue
             })
         });
-
         drop(tx);
-        drop(progress_tx);
 
-        let mut results: Vec<CodeDebtItem> = Vec::new();
-        let mut processed_files = 0;
+        let mut files = Vec::new();
+        for entry in rx {
+            match entry {
+                Ok(file) => files.push(file),
+                Err(file) => skipped.push(file),
+            }
+        }
+        Ok(files)
+    }
 
-        for item in rx.iter() {
-            if item.pattern_type == "__PROGRESS__" {
-                processed_files += 1;
-                if let Some(reporter) = &self.progress_reporter {
-                    reporter.update(processed_files);
+    /// Scan `files` with their sizes in parallel, reporting progress and
+    /// adding the results to `report`.
+    fn scan_paths(&self, files: &[(PathBuf, u64)], report: &mut ScanReport) {
+        let reporter = self.progress_reporter.as_deref();
+        if let Some(reporter) = reporter {
+            reporter.start(files.len(), files.iter().map(|(_, size)| size).sum());
+        }
+
+        let matcher = &self.matcher;
+        let options = &self.options;
+        let outcomes: Vec<FileScan> = files
+            .par_iter()
+            .map(|(path, size)| {
+                let outcome = match read_file(path, *size) {
+                    Ok(bytes) => Self::scan_bytes(path, &bytes, matcher, options),
+                    Err(e) => FileScan::Skipped(SkipReason::Unreadable {
+                        error: e.to_string(),
+                    }),
+                };
+                if let Some(reporter) = reporter {
+                    reporter.file_scanned(path, *size);
+                }
+                outcome
+            })
+            .collect();
+
+        if let Some(reporter) = reporter {
+            reporter.finish();
+        }
+
+        for ((path, _), outcome) in files.iter().zip(outcomes) {
+            outcome.record(path.clone(), report);
+        }
+    }
+
+    /// Decode and scan the raw content of a file, see [`encoding::decode`].
+    pub(crate) fn scan_bytes(
+        path: &Path,
+        bytes: &[u8],
+        matcher: &PatternMatcher,
+        options: &ScanOptions,
+    ) -> FileScan {
+        match encoding::decode(bytes) {
+            Decoded::Text {
+                content,
+                encoding,
+                lossy,
+            } => {
+                let mut items = Self::scan_content(path, &content, matcher, options);
+                encoding::source_offsets(
+                    bytes,
+                    &content,
+                    encoding,
+                    items.iter_mut().map(|item| &mut item.byte_offset),
+                );
+                FileScan::Scanned {
+                    items,
+                    bytes: bytes.len() as u64,
+                    transcoded: (encoding != Encoding::Utf8 || lossy).then_some((encoding, lossy)),
                 }
-            } else {
-                results.push(item);
             }
+            Decoded::Binary => FileScan::Skipped(SkipReason::Binary),
         }
+    }
 
-        if let Some(reporter) = &self.progress_reporter {
-            reporter.finish();
+    /// Scan exactly these files, whatever their extension and regardless of
+    /// ignore rules. Fails if any of them can't be read as text.
+    pub fn scan_files(&self, files: &[PathBuf]) -> Result<Vec<CodeDebtItem>> {
+        let report = self.scan_files_report(files);
+        if let Some(skipped) = report.files_skipped.first() {
+            anyhow::bail!(
+                "Failed to read {}: {}",
+                skipped.path.display(),
+                skipped.reason
+            );
+        }
+        Ok(report.items)
+    }
+
+    /// Like [`CodeDebtScanner::scan_files`], but files that can't be read
+    /// are listed as skipped in the report instead of failing the scan.
+    pub fn scan_files_report(&self, files: &[PathBuf]) -> ScanReport {
+        let started = Instant::now();
+        let mut report = ScanReport::new(files.to_vec(), self.config_hash());
+        let files: Vec<(PathBuf, u64)> = files
+            .iter()
+            .map(|path| {
+                let size = std::fs::metadata(path).map_or(0, |metadata| metadata.len());
+                (path.clone(), size)
+            })
+            .collect();
+        self.scan_paths(&files, &mut report);
+        self.finish_items(&mut report.items);
+        report.duration_ms = started.elapsed().as_millis() as u64;
+        report
+    }
+
+    /// Scan in-memory content, e.g. an unsaved editor buffer. `file_path`
+    /// is reported on the items and selects the language by extension.
+    pub fn scan_source<P: AsRef<Path>>(&self, file_path: P, content: &str) -> Vec<CodeDebtItem> {
+        let mut results =
+            Self::scan_content(file_path.as_ref(), content, &self.matcher, &self.options);
+        self.finish_items(&mut results);
+        results
+    }
+
+    /// Scan the tree of a commit straight from the object database, without
+    /// a working tree, e.g. a tag in a bare mirror. `root_path` locates the
+    /// repository; if it is a subdirectory only files under it are scanned.
+    /// Items are named as a scan of `root_path` would name them, relative
+    /// to the repository root for bare repositories, and are not blamed.
+    pub fn scan_revision<P: AsRef<Path>>(
+        &self,
+        root_path: P,
+        rev: &str,
+    ) -> Result<Vec<CodeDebtItem>> {
+        Ok(self.scan_revision_report(root_path, rev)?.items)
+    }
+
+    /// Like [`CodeDebtScanner::scan_revision`], reporting what was scanned
+    /// and skipped along with the items. Skipped paths are named like items.
+    pub fn scan_revision_report<P: AsRef<Path>>(
+        &self,
+        root_path: P,
+        rev: &str,
+    ) -> Result<ScanReport> {
+        let root_path = root_path.as_ref();
+        let started = Instant::now();
+        let mut report = ScanReport::new(vec![root_path.to_path_buf()], self.config_hash());
+        let (repo, prefix) = revision::open_repository(root_path)?;
+        let tree = repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_tree())
+            .with_context(|| format!("Unknown git revision '{}'", rev))?;
+        let files = TreeWalker::new(self)?.files(&repo, &tree, prefix.as_deref())?;
+
+        let base = if repo.is_bare() {
+            PathBuf::new()
+        } else {
+            root_path.to_path_buf()
+        };
+        let prefix = prefix.unwrap_or_default();
+        let name = |path: &Path| base.join(path.strip_prefix(&prefix).unwrap_or(path));
+        let matcher = &self.matcher;
+        let options = &self.options;
+        let per_file = revision::scan_blobs(&repo, &files, |file, bytes| {
+            Self::scan_bytes(&name(&file.path), bytes, matcher, options)
+        })?;
+        for (file, outcome) in files.iter().zip(per_file) {
+            outcome.record(name(&file.path), &mut report);
         }
+        self.annotate_items(&mut report.items);
+        report.duration_ms = started.elapsed().as_millis() as u64;
+        Ok(report)
+    }
 
+    /// Hash of the settings that decide which files are scanned and what
+    /// is reported, so reports from different configurations can be told
+    /// apart.
+    pub fn config_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        let mut add = |part: &str| {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        };
+        for pattern in self.patterns() {
+            add(&pattern.name);
+            add(pattern.regex.as_str());
+            add(&format!("{:?}", pattern.severity));
+        }
+        for list in [
+            &self.file_extensions,
+            &self.ignore_dirs,
+            &self.include_globs,
+            &self.exclude_globs,
+        ] {
+            let mut sorted: Vec<&String> = list.iter().collect();
+            sorted.sort();
+            add(&sorted.len().to_string());
+            for value in sorted {
+                add(value);
+            }
+        }
+        add(&format!(
+            "{} {} {} {} {}",
+            self.options.comments_only,
+            self.options.include_suppressed,
+            self.options.all_matches,
+            self.enable_git_blame,
+            self.detect_duplicates
+        ));
+        fingerprint::hex(&hasher.finalize()[..16])
+    }
+
+    fn finish_items(&self, results: &mut [CodeDebtItem]) {
         // Add git blame information if enabled
         if self.enable_git_blame {
-            GitAnalyzer::add_git_information(self.git_repo.as_ref(), &mut results);
+            let now = self.reference_time.unwrap_or_else(Utc::now);
+            GitAnalyzer::add_git_information(results, &self.blame_config, now);
         }
+        self.annotate_items(results);
+    }
 
+    fn annotate_items(&self, results: &mut [CodeDebtItem]) {
         // Detect duplicates if enabled
         if self.detect_duplicates {
-            self.detect_duplicate_patterns(&mut results);
+            self.detect_duplicate_patterns(results);
         }
 
         // Add file extension information
-        self.add_file_extensions(&mut results);
+        self.add_file_extensions(results);
 
         results.sort_by(|a, b| {
             a.severity
@@ -193,64 +517,106 @@ impl CodeDebtScanner {
                 .then_with(|| a.file_path.cmp(&b.file_path))
                 .then_with(|| a.line_number.cmp(&b.line_number))
         });
-
-        Ok(results)
-    }
-
-    fn count_files<P: AsRef<Path>>(&self, root_path: P) -> Result<usize> {
-        let extensions: HashSet<String> = self.file_extensions.iter().cloned().collect();
-        let walker = WalkBuilder::new(&root_path)
-            .hidden(false)
-            .ignore(true)
-            .git_ignore(true)
-            .build();
-
-        let count = walker
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| {
-                let path = entry.path();
-                path.is_file()
-                    && path
-                        .extension()
-                        .and_then(|ext| ext.to_str())
-                        .map(|ext| extensions.contains(ext))
-                        .unwrap_or(false)
-            })
-            .count();
-
-        Ok(count)
     }
 
     pub(crate) fn scan_content(
         file_path: &Path,
         content: &str,
-        patterns: &[Pattern],
+        matcher: &PatternMatcher,
+        options: &ScanOptions,
     ) -> Vec<CodeDebtItem> {
-        content
-            .lines()
-            .enumerate()
-            .flat_map(|(line_idx, line)| {
-                patterns
-                    .iter()
-                    .filter_map(|pattern| {
-                        pattern.regex.find(line).map(|m| CodeDebtItem {
-                            file_path: file_path.to_path_buf(),
-                            line_number: line_idx + 1,
-                            column: m.start() + 1,
-                            line_content: line.trim().to_string(),
-                            pattern_type: pattern.name.clone(),
-                            severity: pattern.severity.clone(),
-                            author: None,
-                            age_days: None,
-                            commit_hash: None,
-                            created_at: None,
-                            file_extension: None,
-                            duplicate_count: 0,
-                        })
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect()
+        let candidates = matcher.candidate_lines(content);
+        if candidates.as_ref().is_some_and(Vec::is_empty) {
+            return Vec::new();
+        }
+        let mut candidates = candidates.map(|lines| lines.into_iter().peekable());
+
+        // Files in languages we can't lex are matched on every line, and
+        // their suppression directives are read from the raw text
+        let language = file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Language::for_extension);
+        let spans = language
+            .filter(|_| options.comments_only || content.contains(suppression::DIRECTIVE_PREFIX))
+            .map(|language| language.comment_spans(content));
+        let comments = spans.as_ref().filter(|_| options.comments_only);
+
+        let directive_spans = language.zip(spans.as_ref()).map(|(language, spans)| {
+            spans
+                .iter()
+                .filter(|span| !language.is_doc_comment(&content[(*span).clone()]))
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+        let suppressions = Suppressions::parse(content, directive_spans.as_deref());
+
+        let mut items = Vec::new();
+        let mut line_start = 0;
+
+        for (line_idx, raw_line) in content.split_inclusive('\n').enumerate() {
+            let line = raw_line.strip_suffix('\n').unwrap_or(raw_line);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+
+            // Lines the prefilter found nothing on can't match any pattern
+            let candidate = candidates
+                .as_mut()
+                .is_none_or(|lines| lines.next_if_eq(&line_start).is_some());
+            let patterns = candidate
+                .then(|| matcher.matching(line))
+                .into_iter()
+                .flatten();
+
+            for pattern in patterns {
+                let suppressed = suppressions.is_suppressed(line_idx + 1, &pattern.name);
+                if suppressed && !options.include_suppressed {
+                    continue;
+                }
+
+                let found = pattern.regex.find_iter(line).filter(|m| match comments {
+                    Some(spans) => lexer::in_spans(spans, line_start + m.start()),
+                    None => true,
+                });
+                let limit = if options.all_matches { usize::MAX } else { 1 };
+
+                for m in found.take(limit) {
+                    let annotation = Annotation::parse(&line[m.end()..]);
+                    // Columns count characters so editors can underline
+                    // non-ASCII lines correctly
+                    let column = line[..m.start()].chars().count() + 1;
+                    items.push(CodeDebtItem {
+                        end_column: column + m.as_str().chars().count(),
+                        byte_offset: line_start + m.start(),
+                        matched_text: m.as_str().to_string(),
+                        assignee: annotation.assignee,
+                        issue_refs: annotation.issue_refs,
+                        due_date: annotation.due_date,
+                        message: annotation.message,
+                        suppressed,
+                        ..CodeDebtItem::new(
+                            file_path,
+                            line_idx + 1,
+                            column,
+                            line.trim(),
+                            pattern.name.clone(),
+                            pattern.severity.clone(),
+                        )
+                    });
+                }
+            }
+
+            line_start += raw_line.len();
+        }
+
+        if !items.is_empty() {
+            let lines: Vec<&str> = content.lines().collect();
+            for item in &mut items {
+                item.fingerprint =
+                    fingerprint::fingerprint_in(&item.pattern_type, &lines, item.line_number - 1);
+            }
+        }
+
+        items
     }
 
     pub fn get_summary(&self, items: &[CodeDebtItem]) -> HashMap<String, usize> {
@@ -289,7 +655,7 @@ impl CodeDebtScanner {
         }
     }
 
-    fn add_file_extensions(&self, items: &mut [CodeDebtItem]) {
+    pub(crate) fn add_file_extensions(&self, items: &mut [CodeDebtItem]) {
         for item in items.iter_mut() {
             if let Some(ext) = item.file_path.extension() {
                 item.file_extension = ext.to_str().map(|s| s.to_string());
@@ -306,10 +672,60 @@ impl CodeDebtScanner {
         summary
     }
 
+    /// Rank files by debt times churn over the `days` before the reference
+    /// time, highest first. Suppressed items and files without commits in
+    /// the window are left out.
+    pub fn get_hotspots(&self, items: &[CodeDebtItem], days: i64) -> Vec<Hotspot> {
+        let mut by_file: BTreeMap<&PathBuf, Vec<&CodeDebtItem>> = BTreeMap::new();
+        for item in items.iter().filter(|item| !item.suppressed) {
+            by_file.entry(&item.file_path).or_default().push(item);
+        }
+
+        let until = self.reference_time.unwrap_or_else(Utc::now);
+        let since = until - chrono::Duration::days(days);
+        let paths: Vec<PathBuf> = by_file.keys().map(|path| path.to_path_buf()).collect();
+        let mut churn = GitAnalyzer::file_churn(&paths, since, until);
+
+        let mut hotspots: Vec<Hotspot> = by_file
+            .into_iter()
+            .filter_map(|(path, items)| {
+                let churn = churn.remove(path)?;
+                let mut by_severity = BTreeMap::new();
+                for item in &items {
+                    *by_severity.entry(item.severity.clone()).or_insert(0) += 1;
+                }
+                let debt_score = items.iter().map(|item| item.severity.weight()).sum();
+                Some(Hotspot {
+                    file_path: path.clone(),
+                    items: items.len(),
+                    by_severity,
+                    debt_score,
+                    score: debt_score * churn.commits,
+                    churn,
+                })
+            })
+            .collect();
+        hotspots.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| b.churn.lines_changed().cmp(&a.churn.lines_changed()))
+                .then_with(|| a.file_path.cmp(&b.file_path))
+        });
+        hotspots
+    }
+
     pub fn get_age_distribution(&self, items: &[CodeDebtItem]) -> HashMap<String, usize> {
         let mut distribution = HashMap::new();
         for item in items {
-            if let Some(age) = item.age_days {
+            if let Some(status @ (BlameStatus::Uncommitted | BlameStatus::Untracked)) =
+                item.blame_status
+            {
+                let bucket = match status {
+                    BlameStatus::Uncommitted => "Uncommitted",
+                    _ => "Untracked",
+                };
+                *distribution.entry(bucket.to_string()).or_insert(0) += 1;
+            } else if let Some(age) = item.age_days {
                 let bucket = match age {
                     0..=7 => "This week",
                     8..=30 => "This month",
@@ -336,6 +752,54 @@ impl CodeDebtScanner {
             .collect()
     }
 
+    pub fn filter_by_blame_status(
+        &self,
+        items: &[CodeDebtItem],
+        statuses: &[BlameStatus],
+    ) -> Vec<CodeDebtItem> {
+        items
+            .iter()
+            .filter(|item| {
+                item.blame_status
+                    .is_some_and(|status| statuses.contains(&status))
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn filter_by_assignee(&self, items: &[CodeDebtItem], assignee: &str) -> Vec<CodeDebtItem> {
+        items
+            .iter()
+            .filter(|item| {
+                item.assignee
+                    .as_deref()
+                    .is_some_and(|a| a.eq_ignore_ascii_case(assignee))
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn filter_by_issue(&self, items: &[CodeDebtItem], issue: &str) -> Vec<CodeDebtItem> {
+        items
+            .iter()
+            .filter(|item| {
+                item.issue_refs
+                    .iter()
+                    .any(|r| r.eq_ignore_ascii_case(issue))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Items with a due date on or before `date`.
+    pub fn filter_due_before(&self, items: &[CodeDebtItem], date: NaiveDate) -> Vec<CodeDebtItem> {
+        items
+            .iter()
+            .filter(|item| item.due_date.is_some_and(|due| due <= date))
+            .cloned()
+            .collect()
+    }
+
     pub fn find_duplicates(&self, items: &[CodeDebtItem], min_count: usize) -> Vec<CodeDebtItem> {
         items
             .iter()
@@ -344,3 +808,82 @@ impl CodeDebtScanner {
             .collect()
     }
 }
+
+/// What scanning the content of one file gave.
+pub(crate) enum FileScan {
+    Scanned {
+        items: Vec<CodeDebtItem>,
+        bytes: u64,
+        /// Encoding and lossiness if the file was not plain UTF-8
+        transcoded: Option<(Encoding, bool)>,
+    },
+    Skipped(SkipReason),
+}
+
+impl FileScan {
+    fn record(self, path: PathBuf, report: &mut ScanReport) {
+        match self {
+            FileScan::Scanned {
+                items,
+                bytes,
+                transcoded,
+            } => {
+                report.files_scanned += 1;
+                report.bytes_read += bytes;
+                report.items.extend(items);
+                if let Some((encoding, lossy)) = transcoded {
+                    report.files_transcoded.push(TranscodedFile {
+                        path,
+                        encoding,
+                        lossy,
+                    });
+                }
+            }
+            FileScan::Skipped(reason) => report.files_skipped.push(SkippedFile { path, reason }),
+        }
+    }
+}
+
+/// Files at least this big are memory-mapped instead of read into memory.
+const MMAP_THRESHOLD: u64 = 1 << 20;
+
+/// The content of a file, either read or memory-mapped.
+enum FileContent {
+    Read(Vec<u8>),
+    Mapped(memmap2::Mmap),
+}
+
+impl std::ops::Deref for FileContent {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FileContent::Read(bytes) => bytes,
+            FileContent::Mapped(map) => map,
+        }
+    }
+}
+
+/// Read a file of the given size, mapping big files so they are decoded and
+/// prefiltered in place rather than copied first.
+fn read_file(path: &Path, size: u64) -> std::io::Result<FileContent> {
+    if size < MMAP_THRESHOLD {
+        return std::fs::read(path).map(FileContent::Read);
+    }
+    let file = std::fs::File::open(path)?;
+    // SAFETY: the map is only read, and dropped once this file is scanned.
+    // A file truncated by another process meanwhile can raise SIGBUS, a risk
+    // taken only for files big enough to benefit.
+    unsafe { memmap2::Mmap::map(&file) }.map(FileContent::Mapped)
+}
+
+/// The path an error from the directory walk is about, if it has one.
+fn walk_error_path(error: &ignore::Error) -> Option<&Path> {
+    match error {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            walk_error_path(err)
+        }
+        _ => None,
+    }
+}
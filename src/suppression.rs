@@ -0,0 +1,191 @@
+use crate::lexer;
+use std::collections::HashMap;
+use std::ops::Range;
+
+pub(crate) const DIRECTIVE_PREFIX: &str = "codedebt:";
+
+/// What a suppression directive silences.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scope {
+    All,
+    Patterns(Vec<String>),
+}
+
+impl Scope {
+    fn covers(&self, pattern: &str) -> bool {
+        match self {
+            Scope::All => true,
+            Scope::Patterns(names) => names.iter().any(|name| name == pattern),
+        }
+    }
+
+    fn merge(&mut self, other: Scope) {
+        match (self, other) {
+            (Scope::All, _) => {}
+            (this, Scope::All) => *this = Scope::All,
+            (Scope::Patterns(names), Scope::Patterns(more)) => names.extend(more),
+        }
+    }
+}
+
+/// Inline suppression directives found in a file:
+///
+/// - `codedebt:ignore` silences matches on the same line
+/// - `codedebt:ignore-next-line` silences matches on the following line
+/// - `codedebt:ignore-file` silences matches in the whole file
+///
+/// Each directive may be followed by pattern names, e.g.
+/// `codedebt:ignore MOCK_STUB, TEMPORARY`, to silence only those patterns.
+/// In languages the lexer knows, only directives in comments count, so a
+/// directive quoted in a string literal or in documentation is not one.
+#[derive(Debug, Clone, Default)]
+pub struct Suppressions {
+    file: Option<Scope>,
+    lines: HashMap<usize, Scope>,
+}
+
+impl Suppressions {
+    /// Read the directives in `content`. With `comments`, sorted byte ranges
+    /// of the comments that may hold directives, others are ignored.
+    pub fn parse(content: &str, comments: Option<&[Range<usize>]>) -> Self {
+        let mut suppressions = Suppressions::default();
+        if !content.contains(DIRECTIVE_PREFIX) {
+            return suppressions;
+        }
+
+        let mut line_start = 0;
+        for (line_idx, line) in content.split_inclusive('\n').enumerate() {
+            let line_number = line_idx + 1;
+            let mut searched = 0;
+            while let Some(pos) = line[searched..].find(DIRECTIVE_PREFIX) {
+                let start = searched + pos;
+                searched = start + DIRECTIVE_PREFIX.len();
+                if comments.is_some_and(|spans| !lexer::in_spans(spans, line_start + start)) {
+                    continue;
+                }
+                let rest = &line[searched..];
+                let name_len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+                    .unwrap_or(rest.len());
+                let scope = parse_scope(&rest[name_len..]);
+                match &rest[..name_len] {
+                    "ignore" => suppressions.add_line(line_number, scope),
+                    // Pattern names listed in the directive would match on
+                    // its own line, so that line is covered as well
+                    "ignore-next-line" => {
+                        suppressions.add_line(line_number, scope.clone());
+                        suppressions.add_line(line_number + 1, scope);
+                    }
+                    "ignore-file" => match &mut suppressions.file {
+                        Some(existing) => existing.merge(scope),
+                        None => suppressions.file = Some(scope),
+                    },
+                    _ => {}
+                }
+            }
+            line_start += line.len();
+        }
+
+        suppressions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.file.is_none() && self.lines.is_empty()
+    }
+
+    pub fn is_suppressed(&self, line_number: usize, pattern: &str) -> bool {
+        self.file
+            .as_ref()
+            .is_some_and(|scope| scope.covers(pattern))
+            || self
+                .lines
+                .get(&line_number)
+                .is_some_and(|scope| scope.covers(pattern))
+    }
+
+    fn add_line(&mut self, line_number: usize, scope: Scope) {
+        match self.lines.get_mut(&line_number) {
+            Some(existing) => existing.merge(scope),
+            None => {
+                self.lines.insert(line_number, scope);
+            }
+        }
+    }
+}
+
+/// Pattern names are upper-case identifiers separated by commas or spaces.
+/// Anything else, such as a free-form reason, ends the list.
+fn parse_scope(rest: &str) -> Scope {
+    let mut names = Vec::new();
+    for token in rest
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+    {
+        let name = token.trim_end_matches(':');
+        let valid = name.starts_with(|c: char| c.is_ascii_uppercase())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+        if !valid {
+            break;
+        }
+        names.push(name.to_string());
+        if name.len() != token.len() {
+            break;
+        }
+    }
+
+    if names.is_empty() {
+        Scope::All
+    } else {
+        Scope::Patterns(names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_directives() {
+        let content = "\
+let a = mock(); // codedebt:ignore
+// codedebt:ignore-next-line MOCK_STUB, TEMPORARY
+let b = temp_mock();
+let c = 1; // codedebt:ignore TODO: tracked elsewhere
+";
+        let suppressions = Suppressions::parse(content, None);
+        assert!(suppressions.is_suppressed(1, "MOCK_STUB"));
+        assert!(suppressions.is_suppressed(1, "HACK"));
+        assert!(suppressions.is_suppressed(2, "MOCK_STUB"));
+        assert!(!suppressions.is_suppressed(2, "TODO"));
+        assert!(suppressions.is_suppressed(3, "MOCK_STUB"));
+        assert!(suppressions.is_suppressed(3, "TEMPORARY"));
+        assert!(!suppressions.is_suppressed(3, "TODO"));
+        assert!(suppressions.is_suppressed(4, "TODO"));
+        assert!(!suppressions.is_suppressed(4, "FIXME"));
+    }
+
+    #[test]
+    fn test_file_directive() {
+        let suppressions = Suppressions::parse("// codedebt:ignore-file MOCK_STUB\n\nmock\n", None);
+        assert!(suppressions.is_suppressed(3, "MOCK_STUB"));
+        assert!(!suppressions.is_suppressed(3, "TODO"));
+    }
+
+    #[test]
+    fn test_directives_outside_comments() {
+        let content = "let s = \"// codedebt:ignore-file\"; // TODO\n// codedebt:ignore\n";
+        let comments = lexer::Language::for_extension("rs")
+            .unwrap()
+            .comment_spans(content);
+        let suppressions = Suppressions::parse(content, Some(&comments));
+        assert!(!suppressions.is_suppressed(1, "TODO"));
+        assert!(suppressions.is_suppressed(2, "TODO"));
+    }
+
+    #[test]
+    fn test_no_directives() {
+        assert!(Suppressions::parse("// TODO: codedebt is great\n", None).is_empty());
+    }
+}
@@ -0,0 +1,46 @@
+use git2::{Commit, Oid, Repository, Signature, Time};
+use std::path::Path;
+
+/// Write the given files into the working directory, or delete those with
+/// no content, and commit them on top of HEAD as `author` at `time`
+/// (seconds since the epoch).
+pub fn commit(repo: &Repository, files: &[(&str, Option<&str>)], author: &str, time: i64) -> Oid {
+    let sig = Signature::new(author, "dev@example.com", &Time::new(time, 0)).unwrap();
+    commit_as(repo, files, &sig, &sig)
+}
+
+/// Like [`commit`], with separate author and committer signatures.
+pub fn commit_as(
+    repo: &Repository,
+    files: &[(&str, Option<&str>)],
+    author: &Signature,
+    committer: &Signature,
+) -> Oid {
+    let workdir = repo.workdir().unwrap();
+    let mut index = repo.index().unwrap();
+    for (path, content) in files {
+        let full = workdir.join(path);
+        match content {
+            Some(content) => {
+                std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+                std::fs::write(&full, content).unwrap();
+                index.add_path(Path::new(path)).unwrap();
+            }
+            None => {
+                std::fs::remove_file(&full).unwrap();
+                index.remove_path(Path::new(path)).unwrap();
+            }
+        }
+    }
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let parent: Option<Commit> = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<&Commit> = parent.iter().collect();
+    repo.commit(Some("HEAD"), author, committer, "change", &tree, &parents)
+        .unwrap()
+}
+
+/// The current time, for commits whose age does not matter.
+pub fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
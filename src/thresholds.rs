@@ -0,0 +1,181 @@
+use crate::models::{CodeDebtItem, Severity};
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Limits that make a run fail when the reported items exceed them.
+#[derive(Debug, Clone, Default)]
+pub struct Thresholds {
+    /// Fail if any item is at least this severe
+    pub fail_on: Option<Severity>,
+    /// Fail if there are more items than this in total
+    pub max_items: Option<usize>,
+    /// Fail if there are more items of exactly this severity
+    pub max_per_severity: BTreeMap<Severity, usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    SeverityFound {
+        severity: Severity,
+        count: usize,
+    },
+    TooManyItems {
+        count: usize,
+        max: usize,
+    },
+    TooManyOfSeverity {
+        severity: Severity,
+        count: usize,
+        max: usize,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::SeverityFound { severity, count } => {
+                write!(f, "{} items at {:?} severity or above", count, severity)
+            }
+            Violation::TooManyItems { count, max } => {
+                write!(f, "{} items found, maximum is {}", count, max)
+            }
+            Violation::TooManyOfSeverity {
+                severity,
+                count,
+                max,
+            } => write!(
+                f,
+                "{} {:?} items found, maximum is {}",
+                count, severity, max
+            ),
+        }
+    }
+}
+
+impl Thresholds {
+    /// Parse per-severity limits such as `critical=0,high=10`.
+    pub fn parse_limits(spec: &str) -> Result<BTreeMap<Severity, usize>> {
+        spec.split(',')
+            .filter(|part| !part.trim().is_empty())
+            .map(|part| {
+                let (severity, max) = part
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("Invalid limit '{}', expected SEVERITY=N", part))?;
+                let severity = severity.parse::<Severity>().map_err(|e| anyhow!(e))?;
+                let max = max
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| anyhow!("Invalid limit '{}', expected SEVERITY=N", part))?;
+                Ok((severity, max))
+            })
+            .collect()
+    }
+
+    pub fn evaluate(&self, items: &[CodeDebtItem]) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        if let Some(fail_on) = &self.fail_on {
+            let count = items
+                .iter()
+                .filter(|item| item.severity <= *fail_on)
+                .count();
+            if count > 0 {
+                violations.push(Violation::SeverityFound {
+                    severity: fail_on.clone(),
+                    count,
+                });
+            }
+        }
+
+        if let Some(max) = self.max_items {
+            if items.len() > max {
+                violations.push(Violation::TooManyItems {
+                    count: items.len(),
+                    max,
+                });
+            }
+        }
+
+        for (severity, &max) in &self.max_per_severity {
+            let count = items
+                .iter()
+                .filter(|item| item.severity == *severity)
+                .count();
+            if count > max {
+                violations.push(Violation::TooManyOfSeverity {
+                    severity: severity.clone(),
+                    count,
+                    max,
+                });
+            }
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn items(severities: &[Severity]) -> Vec<CodeDebtItem> {
+        severities
+            .iter()
+            .enumerate()
+            .map(|(i, severity)| {
+                CodeDebtItem::new(
+                    PathBuf::from("test.rs"),
+                    i + 1,
+                    1,
+                    "// TODO",
+                    "TODO",
+                    severity.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let limits = Thresholds::parse_limits("critical=0, HIGH=10").unwrap();
+        assert_eq!(limits.get(&Severity::Critical), Some(&0));
+        assert_eq!(limits.get(&Severity::High), Some(&10));
+        assert!(Thresholds::parse_limits("urgent=1").is_err());
+        assert!(Thresholds::parse_limits("high").is_err());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let found = items(&[Severity::High, Severity::Medium, Severity::Low]);
+
+        let thresholds = Thresholds {
+            fail_on: Some(Severity::Critical),
+            max_items: Some(3),
+            ..Default::default()
+        };
+        assert!(thresholds.evaluate(&found).is_empty());
+
+        let thresholds = Thresholds {
+            fail_on: Some(Severity::High),
+            max_items: Some(2),
+            max_per_severity: Thresholds::parse_limits("low=0").unwrap(),
+        };
+        assert_eq!(
+            thresholds.evaluate(&found),
+            vec![
+                Violation::SeverityFound {
+                    severity: Severity::High,
+                    count: 1
+                },
+                Violation::TooManyItems { count: 3, max: 2 },
+                Violation::TooManyOfSeverity {
+                    severity: Severity::Low,
+                    count: 1,
+                    max: 0
+                },
+            ]
+        );
+    }
+}